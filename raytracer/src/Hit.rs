@@ -2,7 +2,10 @@ use std::sync::Arc;
 
 use rand::{thread_rng, Rng};
 
-use crate::bvh::aabb::{surrounding_box, AABB};
+use crate::bvh::{
+    aabb::{surrounding_box, AABB},
+    stats::BvhStats,
+};
 pub use crate::{
     basic::{
         random_double,
@@ -13,6 +16,7 @@ pub use crate::{
     object::sphere::Sphere,
 };
 
+#[derive(Clone, Copy)]
 pub struct HitRecord<'a> {
     pub p: Point3,        // 碰撞点
     pub normal: Vec3,     // 碰撞点的单 位 法 向 量(与Ray的方向相反)
@@ -58,11 +62,49 @@ impl<'a> HitRecord<'a> {
 pub trait Hittable: Send + Sync {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB>;
+    // 阴影射线: 只关心(t_min, t_max)内是否有交点, 不需要最近的交点和HitRecord
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.hit(r, t_min, t_max).is_some()
+    }
+    // 阴影射线的透射率: 不透明物体只有0或1, 介质可以给出中间值以减小方差
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        if self.occluded(r, t_min, t_max) {
+            0.
+        } else {
+            1.
+        }
+    }
+    // 作为光源时: 从 o 看向 v 方向的立体角密度, 以及按该密度采样的方向
+    // 没有实现的物体不能加入 lights
     fn pdf_value(&self, _o: &Point3, _v: &Vec3) -> f64 {
-        0.0
+        panic!("pdf_value is not implemented, this object can't be sampled as a light")
     }
     fn random(&self, _o: &Vec3) -> Vec3 {
-        Vec3::new(1., 0., 0.)
+        panic!("random is not implemented, this object can't be sampled as a light")
+    }
+    // 是否实现了 pdf_value 与 random
+    fn light_samplable(&self) -> bool {
+        false
+    }
+    // 作为光源时的总功率, 用于按功率选择光源; 无法估计时返回None
+    fn power(&self) -> Option<f64> {
+        None
+    }
+    // 动画: 拓扑不变, 按新的快门区间重新计算缓存的包围盒(默认没有缓存)
+    fn refit(&mut self, _time0: f64, _time1: f64) {}
+    // 重建BVH: 把节点中的物体放回 objects, 返回自身是否为BVH节点(默认不是, 由调用者放入自身)
+    fn collect_objects(&mut self, _objects: &mut Vec<Arc<dyn Hittable>>) -> bool {
+        false
+    }
+    // 光线击中包围盒后的期望求交代价, 单个物体记为一次求交
+    fn sah_cost(&self, _time0: f64, _time1: f64) -> f64 {
+        1.
+    }
+    // 统计BVH结构, 返回自身是否为BVH节点(默认记为一个物体)
+    fn bvh_stats(&self, _depth: usize, stats: &mut BvhStats) -> bool {
+        stats.primitive_count += 1;
+        stats.memory += std::mem::size_of_val(self);
+        false
     }
 }
 
@@ -93,6 +135,19 @@ impl Hittable for HittableList {
         }
         hit_rec
     }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.objects.iter().any(|obj| obj.occluded(r, t_min, t_max))
+    }
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let mut transmittance = 1.;
+        for obj in &self.objects {
+            transmittance *= obj.transmittance(r, t_min, t_max);
+            if transmittance <= 0. {
+                return 0.;
+            }
+        }
+        transmittance
+    }
 
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
         if self.objects.is_empty() {
@@ -133,4 +188,31 @@ impl Hittable for HittableList {
     fn random(&self, o: &Vec3) -> Vec3 {
         self.objects[thread_rng().gen_range(0..self.objects.len())].random(o)
     }
+    fn light_samplable(&self) -> bool {
+        !self.objects.is_empty() && self.objects.iter().all(|object| object.light_samplable())
+    }
+    fn power(&self) -> Option<f64> {
+        self.objects.iter().map(|object| object.power()).sum()
+    }
+
+    fn refit(&mut self, time0: f64, time1: f64) {
+        // 被共享的物体无法修改, 只能沿用其自身的包围盒
+        for obj in self.objects.iter_mut() {
+            if let Some(obj) = Arc::get_mut(obj) {
+                obj.refit(time0, time1);
+            }
+        }
+    }
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        self.objects
+            .iter()
+            .map(|obj| obj.sah_cost(time0, time1))
+            .sum()
+    }
+    fn bvh_stats(&self, depth: usize, stats: &mut BvhStats) -> bool {
+        for obj in &self.objects {
+            obj.bvh_stats(depth, stats);
+        }
+        false
+    }
 }
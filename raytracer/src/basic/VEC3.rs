@@ -66,6 +66,10 @@ impl Vec3 {
     pub fn unit_vector(&self) -> Vec3 {
         *self / self.len()
     }
+    // 作为线性 sRGB 颜色时的亮度
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
+    }
 
     // ---- random ----
     pub fn random() -> Self {
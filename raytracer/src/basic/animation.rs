@@ -0,0 +1,273 @@
+use std::ops::Mul;
+
+use super::{
+    degree_to_radians,
+    transform::{Matrix4, Transform},
+    VEC3::Vec3,
+};
+
+// 单位四元数表示旋转, 便于插值(slerp)
+#[derive(Copy, Clone, Debug)]
+pub struct Quaternion {
+    pub w: f64,
+    pub v: Vec3,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Quaternion {
+    pub fn identity() -> Self {
+        Self {
+            w: 1.,
+            v: Vec3::new(0., 0., 0.),
+        }
+    }
+    // 绕 axis 逆时针旋转 angle 度
+    pub fn from_axis_angle(axis: Vec3, angle: f64) -> Self {
+        let (sin, cos) = (degree_to_radians(angle) / 2.).sin_cos();
+        Self {
+            w: cos,
+            v: axis.unit_vector() * sin,
+        }
+    }
+
+    pub fn dot(&self, rhs: &Self) -> f64 {
+        self.w * rhs.w + Vec3::dot(&self.v, &rhs.v)
+    }
+    pub fn normalize(&self) -> Self {
+        let len = self.dot(self).sqrt();
+        Self {
+            w: self.w / len,
+            v: self.v / len,
+        }
+    }
+
+    // 球面线性插值, 走较短的一条弧
+    pub fn slerp(a: &Self, b: &Self, t: f64) -> Self {
+        let mut b = *b;
+        let mut cos = a.dot(&b);
+        if cos < 0. {
+            b = Self { w: -b.w, v: -b.v };
+            cos = -cos;
+        }
+
+        let (wa, wb) = if cos > 0.9995 {
+            // 夹角很小时退化为线性插值
+            (1. - t, t)
+        } else {
+            let theta = cos.acos();
+            let sin = theta.sin();
+            (((1. - t) * theta).sin() / sin, (t * theta).sin() / sin)
+        };
+        Self {
+            w: wa * a.w + wb * b.w,
+            v: wa * a.v + wb * b.v,
+        }
+        .normalize()
+    }
+
+    pub fn to_transform(&self) -> Transform {
+        let Self { w, v } = self.normalize();
+        let (x, y, z) = (v.x, v.y, v.z);
+        let m = Matrix4::new([
+            [
+                1. - 2. * (y * y + z * z),
+                2. * (x * y - w * z),
+                2. * (x * z + w * y),
+                0.,
+            ],
+            [
+                2. * (x * y + w * z),
+                1. - 2. * (x * x + z * z),
+                2. * (y * z - w * x),
+                0.,
+            ],
+            [
+                2. * (x * z - w * y),
+                2. * (y * z + w * x),
+                1. - 2. * (x * x + y * y),
+                0.,
+            ],
+            [0., 0., 0., 1.],
+        ]);
+        Transform {
+            m,
+            m_inv: m.transpose(),
+        }
+    }
+}
+
+// 复合旋转: (a * b) 表示先转 b 再转 a
+impl Mul for Quaternion {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self {
+            w: self.w * rhs.w - Vec3::dot(&self.v, &rhs.v),
+            v: self.w * rhs.v + rhs.w * self.v + self.v.cross(rhs.v),
+        }
+    }
+}
+
+// 关键帧: 变换按 缩放 -> 旋转 -> 平移 的顺序作用在物体上
+#[derive(Copy, Clone, Debug)]
+pub struct Keyframe {
+    pub time: f64,
+    pub translation: Vec3,
+    pub rotation: Quaternion,
+    pub scale: Vec3,
+}
+
+impl Keyframe {
+    pub fn new(time: f64, translation: Vec3, rotation: Quaternion, scale: Vec3) -> Self {
+        Self {
+            time,
+            translation,
+            rotation,
+            scale,
+        }
+    }
+    // 只有平移
+    pub fn at(time: f64, translation: Vec3) -> Self {
+        Self::new(
+            time,
+            translation,
+            Quaternion::identity(),
+            Vec3::new(1., 1., 1.),
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Interpolation {
+    Step,   // 保持前一关键帧, 到下一关键帧时跳变
+    Linear, // 平移/缩放线性插值, 旋转slerp
+    Smooth, // 同 Linear, 但在关键帧处速度为0 (smoothstep)
+}
+
+#[derive(Clone)]
+pub struct AnimatedTransform {
+    keys: Vec<Keyframe>,
+    interpolation: Interpolation,
+}
+
+impl AnimatedTransform {
+    pub fn new(mut keys: Vec<Keyframe>, interpolation: Interpolation) -> Self {
+        assert!(!keys.is_empty(), "animation needs at least one keyframe");
+        keys.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        Self {
+            keys,
+            interpolation,
+        }
+    }
+
+    pub fn keys(&self) -> &[Keyframe] {
+        &self.keys
+    }
+
+    // 第一个关键帧之前/最后一个关键帧之后保持不动
+    pub fn at(&self, time: f64) -> Transform {
+        let first = &self.keys[0];
+        let last = &self.keys[self.keys.len() - 1];
+        if time <= first.time {
+            return Self::compose(first);
+        }
+        if time >= last.time {
+            return Self::compose(last);
+        }
+
+        let next = self.keys.iter().position(|key| key.time > time).unwrap();
+        let k0 = &self.keys[next - 1];
+        let k1 = &self.keys[next];
+
+        let mut t = (time - k0.time) / (k1.time - k0.time);
+        match self.interpolation {
+            Interpolation::Step => return Self::compose(k0),
+            Interpolation::Linear => {}
+            Interpolation::Smooth => t = t * t * (3. - 2. * t),
+        }
+
+        Self::compose(&Keyframe::new(
+            time,
+            k0.translation + (k1.translation - k0.translation) * t,
+            Quaternion::slerp(&k0.rotation, &k1.rotation, t),
+            k0.scale + (k1.scale - k0.scale) * t,
+        ))
+    }
+
+    fn compose(key: &Keyframe) -> Transform {
+        Transform::translate(key.translation)
+            * key.rotation.to_transform()
+            * Transform::scale(key.scale.x, key.scale.y, key.scale.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same_rotation(a: &Quaternion, b: &Quaternion) {
+        // q 与 -q 表示同一个旋转
+        assert!((a.dot(b).abs() - 1.).abs() < 1e-9, "{:?} {:?}", a, b);
+    }
+
+    #[test]
+    fn slerp_endpoints_and_midpoint() {
+        let a = Quaternion::identity();
+        let b = Quaternion::from_axis_angle(Vec3::new(0., 1., 0.), 90.);
+        assert_same_rotation(&Quaternion::slerp(&a, &b, 0.), &a);
+        assert_same_rotation(&Quaternion::slerp(&a, &b, 1.), &b);
+
+        let mid = Quaternion::slerp(&a, &b, 0.5);
+        assert!((mid.dot(&mid) - 1.).abs() < 1e-9);
+        assert_same_rotation(
+            &mid,
+            &Quaternion::from_axis_angle(Vec3::new(0., 1., 0.), 45.),
+        );
+    }
+
+    #[test]
+    fn slerp_takes_shorter_arc() {
+        let a = Quaternion::identity();
+        let b = Quaternion::from_axis_angle(Vec3::new(0., 0., 1.), 60.);
+        let neg_b = Quaternion { w: -b.w, v: -b.v };
+        assert_same_rotation(
+            &Quaternion::slerp(&a, &neg_b, 0.5),
+            &Quaternion::from_axis_angle(Vec3::new(0., 0., 1.), 30.),
+        );
+    }
+
+    #[test]
+    fn quaternion_matches_rotation_matrix() {
+        let axis = Vec3::new(1., 2., -1.);
+        let q = Quaternion::from_axis_angle(axis, 50.).to_transform();
+        let m = Transform::rotate(axis, 50.);
+        let v = Vec3::new(0.3, -0.7, 2.);
+        assert!((q.vector(&v) - m.vector(&v)).len() < 1e-9);
+    }
+
+    #[test]
+    fn keyframe_interpolation() {
+        let keys = vec![
+            Keyframe::at(1., Vec3::new(2., 0., 0.)),
+            Keyframe::at(0., Vec3::new(0., 0., 0.)),
+        ];
+        let origin = Vec3::new(0., 0., 0.);
+        let linear = AnimatedTransform::new(keys.clone(), Interpolation::Linear);
+        assert!((linear.at(0.25).point(&origin) - Vec3::new(0.5, 0., 0.)).len() < 1e-9);
+        // 区间外保持首尾关键帧
+        assert!((linear.at(-1.).point(&origin) - origin).len() < 1e-9);
+        assert!((linear.at(2.).point(&origin) - Vec3::new(2., 0., 0.)).len() < 1e-9);
+
+        let step = AnimatedTransform::new(keys.clone(), Interpolation::Step);
+        assert!((step.at(0.9).point(&origin) - origin).len() < 1e-9);
+
+        let smooth = AnimatedTransform::new(keys, Interpolation::Smooth);
+        assert!((smooth.at(0.5).point(&origin) - Vec3::new(1., 0., 0.)).len() < 1e-9);
+        assert!(smooth.at(0.1).point(&origin).x < linear.at(0.1).point(&origin).x);
+    }
+}
@@ -1,6 +1,8 @@
 pub mod RAY;
 pub mod VEC3;
+pub mod animation;
 pub mod camera;
+pub mod transform;
 
 use std::f64::consts::PI;
 
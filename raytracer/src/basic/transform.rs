@@ -0,0 +1,394 @@
+#![allow(clippy::needless_range_loop)]
+use std::ops::Mul;
+
+use super::{
+    degree_to_radians,
+    RAY::Ray,
+    VEC3::{Point3, Vec3},
+};
+use crate::bvh::aabb::AABB;
+
+// 齐次坐标下的 4x4 矩阵, m[i][j] 为第i行第j列
+#[derive(Copy, Clone, Debug)]
+pub struct Matrix4 {
+    pub m: [[f64; 4]; 4],
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Matrix4 {
+    pub fn new(m: [[f64; 4]; 4]) -> Self {
+        Self { m }
+    }
+    pub fn identity() -> Self {
+        let mut m = [[0.; 4]; 4];
+        for i in 0..4 {
+            m[i][i] = 1.;
+        }
+        Self { m }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.; 4]; 4];
+        for i in 0..4 {
+            for j in 0..4 {
+                m[i][j] = self.m[j][i];
+            }
+        }
+        Self { m }
+    }
+
+    // 左上角 3x3 部分(线性部分)的行列式
+    pub fn det3(&self) -> f64 {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    // Gauss-Jordan 消元(列主元), 奇异矩阵返回None
+    pub fn inverse(&self) -> Option<Self> {
+        let mut a = self.m;
+        let mut inv = Self::identity().m;
+
+        for col in 0..4 {
+            let mut pivot = col;
+            for row in col + 1..4 {
+                if a[row][col].abs() > a[pivot][col].abs() {
+                    pivot = row;
+                }
+            }
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1. / a[col][col];
+            for j in 0..4 {
+                a[col][j] *= scale;
+                inv[col][j] *= scale;
+            }
+            for row in 0..4 {
+                if row != col {
+                    let factor = a[row][col];
+                    for j in 0..4 {
+                        a[row][j] -= factor * a[col][j];
+                        inv[row][j] -= factor * inv[col][j];
+                    }
+                }
+            }
+        }
+        Some(Self { m: inv })
+    }
+
+    pub fn point(&self, p: &Point3) -> Point3 {
+        let m = &self.m;
+        let x = m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3];
+        let y = m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3];
+        let z = m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3];
+        let w = m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3];
+        if w == 1. {
+            Point3::new(x, y, z)
+        } else {
+            Point3::new(x, y, z) / w
+        }
+    }
+    // 方向向量不受平移影响
+    pub fn vector(&self, v: &Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+}
+
+impl Mul for Matrix4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut m = [[0.; 4]; 4];
+        for i in 0..4 {
+            for j in 0..4 {
+                for k in 0..4 {
+                    m[i][j] += self.m[i][k] * rhs.m[k][j];
+                }
+            }
+        }
+        Self { m }
+    }
+}
+
+// 仿射变换, 同时保存其逆矩阵
+// 复合: (a * b) 表示先做 b 再做 a
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Transform {
+    pub m: Matrix4,
+    pub m_inv: Matrix4,
+}
+
+impl Transform {
+    pub fn new(m: Matrix4) -> Self {
+        let m_inv = m.inverse().expect("transform matrix is singular");
+        Self { m, m_inv }
+    }
+    pub fn identity() -> Self {
+        Self::default()
+    }
+
+    pub fn translate(offset: Vec3) -> Self {
+        let mut m = Matrix4::identity();
+        let mut m_inv = Matrix4::identity();
+        for i in 0..3 {
+            m.m[i][3] = offset[i as u32];
+            m_inv.m[i][3] = -offset[i as u32];
+        }
+        Self { m, m_inv }
+    }
+    pub fn scale(x: f64, y: f64, z: f64) -> Self {
+        let m = Matrix4::new([
+            [x, 0., 0., 0.],
+            [0., y, 0., 0.],
+            [0., 0., z, 0.],
+            [0., 0., 0., 1.],
+        ]);
+        let m_inv = Matrix4::new([
+            [1. / x, 0., 0., 0.],
+            [0., 1. / y, 0., 0.],
+            [0., 0., 1. / z, 0.],
+            [0., 0., 0., 1.],
+        ]);
+        Self { m, m_inv }
+    }
+
+    // 旋转均为右手系下绕轴逆时针 angle 度, 逆矩阵即为转置
+    pub fn rotate_x(angle: f64) -> Self {
+        let (sin, cos) = degree_to_radians(angle).sin_cos();
+        let m = Matrix4::new([
+            [1., 0., 0., 0.],
+            [0., cos, -sin, 0.],
+            [0., sin, cos, 0.],
+            [0., 0., 0., 1.],
+        ]);
+        Self {
+            m,
+            m_inv: m.transpose(),
+        }
+    }
+    pub fn rotate_y(angle: f64) -> Self {
+        let (sin, cos) = degree_to_radians(angle).sin_cos();
+        let m = Matrix4::new([
+            [cos, 0., sin, 0.],
+            [0., 1., 0., 0.],
+            [-sin, 0., cos, 0.],
+            [0., 0., 0., 1.],
+        ]);
+        Self {
+            m,
+            m_inv: m.transpose(),
+        }
+    }
+    pub fn rotate_z(angle: f64) -> Self {
+        let (sin, cos) = degree_to_radians(angle).sin_cos();
+        let m = Matrix4::new([
+            [cos, -sin, 0., 0.],
+            [sin, cos, 0., 0.],
+            [0., 0., 1., 0.],
+            [0., 0., 0., 1.],
+        ]);
+        Self {
+            m,
+            m_inv: m.transpose(),
+        }
+    }
+    // 绕任意轴旋转 (Rodrigues)
+    pub fn rotate(axis: Vec3, angle: f64) -> Self {
+        let a = axis.unit_vector();
+        let (sin, cos) = degree_to_radians(angle).sin_cos();
+        let m = Matrix4::new([
+            [
+                a.x * a.x + (1. - a.x * a.x) * cos,
+                a.x * a.y * (1. - cos) - a.z * sin,
+                a.x * a.z * (1. - cos) + a.y * sin,
+                0.,
+            ],
+            [
+                a.x * a.y * (1. - cos) + a.z * sin,
+                a.y * a.y + (1. - a.y * a.y) * cos,
+                a.y * a.z * (1. - cos) - a.x * sin,
+                0.,
+            ],
+            [
+                a.x * a.z * (1. - cos) - a.y * sin,
+                a.y * a.z * (1. - cos) + a.x * sin,
+                a.z * a.z + (1. - a.z * a.z) * cos,
+                0.,
+            ],
+            [0., 0., 0., 1.],
+        ]);
+        Self {
+            m,
+            m_inv: m.transpose(),
+        }
+    }
+    // 错切: x += xy * y + xz * z, 其余同理
+    pub fn shear(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Self {
+        Self::new(Matrix4::new([
+            [1., xy, xz, 0.],
+            [yx, 1., yz, 0.],
+            [zx, zy, 1., 0.],
+            [0., 0., 0., 1.],
+        ]))
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            m: self.m_inv,
+            m_inv: self.m,
+        }
+    }
+
+    pub fn point(&self, p: &Point3) -> Point3 {
+        self.m.point(p)
+    }
+    pub fn vector(&self, v: &Vec3) -> Vec3 {
+        self.m.vector(v)
+    }
+    // 法向量需要乘逆矩阵的转置才能保持与切平面垂直(结果未单位化)
+    pub fn normal(&self, n: &Vec3) -> Vec3 {
+        self.m_inv.transpose().vector(n)
+    }
+    // 方向不单位化, 因此变换前后光线参数 t 不变
+    pub fn ray(&self, r: &Ray) -> Ray {
+        Ray::new(
+            self.point(&r.origin()),
+            self.vector(&r.direction()),
+            r.time(),
+        )
+    }
+    // 世界空间方向 w 对应的物体空间方向 w' = M^-1 w / |M^-1 w|,
+    // 以及立体角之间的雅可比行列式 dw'/dw = |det M^-1| / |M^-1 w|^3 (w 为单位向量)
+    pub fn local_direction(&self, w: &Vec3) -> (Vec3, f64) {
+        let local = self.m_inv.vector(&w.unit_vector());
+        let len = local.len();
+        (local / len, self.m_inv.det3().abs() / len.powi(3))
+    }
+
+    // 表面积的缩放倍数: 对均匀缩放(及旋转, 平移)精确, 非均匀缩放时取各方向的几何平均
+    pub fn area_scale(&self) -> f64 {
+        self.m.det3().abs().powf(2. / 3.)
+    }
+
+    // 变换后包围盒8个顶点的包围盒
+    pub fn bounding_box(&self, bbox: &AABB) -> AABB {
+        let mut mi = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut mx = -mi;
+
+        for i in 0..2 {
+            for j in 0..2 {
+                for k in 0..2 {
+                    let corner = Point3::new(
+                        if i == 0 { bbox.mini.x } else { bbox.maxi.x },
+                        if j == 0 { bbox.mini.y } else { bbox.maxi.y },
+                        if k == 0 { bbox.mini.z } else { bbox.maxi.z },
+                    );
+                    let tester = self.point(&corner);
+                    for c in 0..3 {
+                        mi[c] = mi[c].min(tester[c]);
+                        mx[c] = mx[c].max(tester[c]);
+                    }
+                }
+            }
+        }
+        AABB::new(mi, mx)
+    }
+}
+
+impl Mul for Transform {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self {
+            m: self.m * rhs.m,
+            m_inv: rhs.m_inv * self.m_inv,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_identity(m: &Matrix4) {
+        for i in 0..4 {
+            for j in 0..4 {
+                let expected = if i == j { 1. } else { 0. };
+                assert!((m.m[i][j] - expected).abs() < 1e-9, "{:?}", m);
+            }
+        }
+    }
+
+    fn composed() -> Transform {
+        Transform::translate(Vec3::new(1., -2., 3.))
+            * Transform::rotate(Vec3::new(1., 2., 3.), 37.)
+            * Transform::shear(0.3, 0., 0., -0.2, 0.1, 0.)
+            * Transform::scale(2., 0.5, 3.)
+    }
+
+    #[test]
+    fn inverse() {
+        let m = composed().m;
+        let inv = m.inverse().unwrap();
+        assert_identity(&(m * inv));
+        assert_identity(&(inv * m));
+
+        let singular = Transform::scale(1., 0., 1.).m;
+        assert!(singular.inverse().is_none());
+    }
+
+    #[test]
+    fn stored_inverse() {
+        for t in [
+            Transform::translate(Vec3::new(1., 2., 3.)),
+            Transform::scale(2., 3., 4.),
+            Transform::rotate_x(30.),
+            Transform::rotate_y(45.),
+            Transform::rotate_z(60.),
+            Transform::rotate(Vec3::new(1., 1., 0.), 75.),
+            composed(),
+        ]
+        .iter()
+        {
+            assert_identity(&(t.m * t.m_inv));
+        }
+    }
+
+    #[test]
+    fn normal_stays_perpendicular() {
+        let t = composed();
+        // 平面 x + y + z = 0 的法向与其上的两个切向
+        let n = Vec3::new(1., 1., 1.);
+        let tangents = [Vec3::new(1., -1., 0.), Vec3::new(0., 1., -1.)];
+        let tn = t.normal(&n);
+        for tangent in tangents.iter() {
+            assert!(Vec3::dot(&tn, &t.vector(tangent)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn point_and_vector() {
+        let t = Transform::translate(Vec3::new(1., 2., 3.)) * Transform::rotate_z(90.);
+        let p = t.point(&Point3::new(1., 0., 0.));
+        assert!((p - Point3::new(1., 3., 3.)).len() < 1e-9);
+        let v = t.vector(&Vec3::new(1., 0., 0.));
+        assert!((v - Vec3::new(0., 1., 0.)).len() < 1e-9);
+        let back = t.inverse().point(&p);
+        assert!((back - Point3::new(1., 0., 0.)).len() < 1e-9);
+    }
+}
@@ -1,5 +1,7 @@
 use crate::Hit::{Point3, Ray};
 
+use super::stats::count_aabb_test;
+
 #[derive(Default, Copy, Clone, Debug)]
 pub struct AABB {
     pub mini: Point3,
@@ -12,6 +14,11 @@ impl AABB {
     }
 
     pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.clip(r, t_min, t_max).is_some()
+    }
+    // 光线在包围盒内的参数区间
+    pub fn clip(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        count_aabb_test();
         let mut tmin = t_min;
         let mut tmax = t_max;
         for a in 0..3 {
@@ -27,10 +34,16 @@ impl AABB {
             tmax = tmax.min(t1);
 
             if tmax <= tmin {
-                return false;
+                return None;
             }
         }
-        true
+        Some((tmin, tmax))
+    }
+
+    // 表面积, 用于SAH代价估计
+    pub fn area(&self) -> f64 {
+        let d = self.maxi - self.mini;
+        2. * (d.x * d.y + d.y * d.z + d.z * d.x)
     }
 }
 
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use crate::Hit::{HitRecord, Hittable, HittableList, Point3, Ray, Vec3};
+
+use super::{aabb::AABB, bvh_node::BvhNode, stats::BvhStats};
+
+// 用于动画的BVH: 每帧只refit包围盒, 拓扑不变; 当SAH代价比构建时恶化超过一定比例后才重建
+// 物体只由BVH持有, refit才能修改其缓存的包围盒; 同时加入光源列表的物体不能缓存随时间变化的包围盒
+pub struct AnimatedBvh {
+    root: Arc<BvhNode>,
+    build_cost: f64,   // 最近一次构建时的SAH代价
+    rebuild_rate: f64, // 例: 0.5 表示代价增加50%后重建, INFINITY 表示从不重建
+}
+
+impl AnimatedBvh {
+    pub fn new(objects: HittableList, time0: f64, time1: f64, rebuild_rate: f64) -> Self {
+        let root = BvhNode::new(objects, time0, time1);
+        let build_cost = root.sah_cost(time0, time1);
+        Self {
+            root: Arc::new(root),
+            build_cost,
+            rebuild_rate,
+        }
+    }
+
+    // 渲染每一帧之前调用, [time0, time1] 为该帧的快门区间. 返回是否重建了BVH
+    pub fn update(&mut self, time0: f64, time1: f64) -> bool {
+        let root = Arc::get_mut(&mut self.root).expect("BVH is still used by the last frame");
+        root.refit(time0, time1);
+
+        if root.sah_cost(time0, time1) <= self.build_cost * (1. + self.rebuild_rate) {
+            return false;
+        }
+
+        // 从旧的BVH中取回所有物体; 旧的BVH释放后物体重新只由新的BVH持有
+        let mut objects = Vec::new();
+        root.collect_objects(&mut objects);
+        let root = BvhNode::new_from_vec(objects, time0, time1);
+        self.build_cost = root.sah_cost(time0, time1);
+        self.root = Arc::new(root);
+        true
+    }
+
+    // 交给渲染线程的场景, 在下一次 update 之前需要全部释放
+    pub fn root(&self) -> Arc<BvhNode> {
+        self.root.clone()
+    }
+}
+
+impl Hittable for AnimatedBvh {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.root.hit(r, t_min, t_max)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.root.occluded(r, t_min, t_max)
+    }
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        self.root.transmittance(r, t_min, t_max)
+    }
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
+        self.root.bounding_box(time0, time1)
+    }
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        self.root.pdf_value(o, v)
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        self.root.random(o)
+    }
+    fn light_samplable(&self) -> bool {
+        self.root.light_samplable()
+    }
+    fn power(&self) -> Option<f64> {
+        self.root.power()
+    }
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        self.root.sah_cost(time0, time1)
+    }
+    fn bvh_stats(&self, depth: usize, stats: &mut BvhStats) -> bool {
+        self.root.bvh_stats(depth, stats)
+    }
+}
@@ -1,11 +1,17 @@
 use core::panic;
-use std::{cmp::Ordering, sync::Arc};
+use std::{cmp::Ordering, f64::INFINITY, sync::Arc};
 
 use rand::{thread_rng, Rng};
 
-use crate::Hit::{Hittable, HittableList};
+use crate::Hit::{random_double, Hittable, HittableList, Point3, Ray, Vec3};
 
-use super::aabb::{surrounding_box, AABB};
+use super::{
+    aabb::{surrounding_box, AABB},
+    stats::BvhStats,
+};
+
+// 遍历一个节点相对于一次求交的代价
+pub const TRAVERSAL_COST: f64 = 0.125;
 
 pub struct BvhNode {
     left: Arc<dyn Hittable>, // 指向 Hittable List
@@ -54,8 +60,8 @@ impl BvhNode {
 
         let compare = |x: &Arc<dyn Hittable>, y: &Arc<dyn Hittable>| {
             f64::partial_cmp(
-                &x.bounding_box(0., 0.).unwrap().mini[axis],
-                &y.bounding_box(0., 0.).unwrap().mini[axis],
+                &x.bounding_box(time0, time1).unwrap().mini[axis],
+                &y.bounding_box(time0, time1).unwrap().mini[axis],
             )
             .unwrap()
         };
@@ -115,6 +121,14 @@ impl BvhNode {
 
         Self::new_node(left, right, box_cur)
     }
+
+    // 子节点展开为其中的物体, 叶子直接放入
+    fn collect_child(child: &mut Arc<dyn Hittable>, objects: &mut Vec<Arc<dyn Hittable>>) {
+        let is_node = Arc::get_mut(child).map_or(false, |child| child.collect_objects(objects));
+        if !is_node {
+            objects.push(child.clone());
+        }
+    }
 }
 
 impl Hittable for BvhNode {
@@ -142,4 +156,108 @@ impl Hittable for BvhNode {
             None => hit_left,
         }
     }
+
+    fn occluded(&self, r: &crate::Hit::Ray, t_min: f64, t_max: f64) -> bool {
+        self.box_aabb.hit(r, t_min, t_max)
+            && (self.left.occluded(r, t_min, t_max) || self.right.occluded(r, t_min, t_max))
+    }
+    fn transmittance(&self, r: &crate::Hit::Ray, t_min: f64, t_max: f64) -> f64 {
+        if !self.box_aabb.hit(r, t_min, t_max) {
+            return 1.;
+        }
+        // 叶子节点左右是同一物体, 只算一次
+        let left = self.left.transmittance(r, t_min, t_max);
+        if left <= 0. || Arc::ptr_eq(&self.left, &self.right) {
+            return left;
+        }
+        left * self.right.transmittance(r, t_min, t_max)
+    }
+
+    // 作为光源时左右子树各以 1/2 的概率被选中; 光线没有穿过包围盒时密度为0
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        if !self.box_aabb.hit(&Ray::new(*o, *v, 0.), 0.001, INFINITY) {
+            return 0.;
+        }
+        let left = self.left.pdf_value(o, v);
+        if Arc::ptr_eq(&self.left, &self.right) {
+            return left;
+        }
+        0.5 * (left + self.right.pdf_value(o, v))
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        if random_double() < 0.5 {
+            self.left.random(o)
+        } else {
+            self.right.random(o)
+        }
+    }
+    fn light_samplable(&self) -> bool {
+        self.left.light_samplable() && self.right.light_samplable()
+    }
+    fn power(&self) -> Option<f64> {
+        if Arc::ptr_eq(&self.left, &self.right) {
+            return self.left.power();
+        }
+        Some(self.left.power()? + self.right.power()?)
+    }
+
+    fn refit(&mut self, time0: f64, time1: f64) {
+        // 自底向上: 先refit子树
+        // 叶子节点(span == 1)左右共享同一物体, 先暂时释放右侧的引用, 使 get_mut 能拿到该物体
+        let shared = Arc::ptr_eq(&self.left, &self.right);
+        if shared {
+            self.right = Arc::new(HittableList::default());
+        }
+        // 同时被别处(如光源列表)持有的物体无法修改, 只能沿用其自身的包围盒
+        if let Some(left) = Arc::get_mut(&mut self.left) {
+            left.refit(time0, time1);
+        }
+        if shared {
+            self.right = self.left.clone();
+        } else if let Some(right) = Arc::get_mut(&mut self.right) {
+            right.refit(time0, time1);
+        }
+
+        let box_left = self.left.bounding_box(time0, time1).unwrap();
+        let box_right = self.right.bounding_box(time0, time1).unwrap();
+        self.box_aabb = surrounding_box(box_left, box_right);
+    }
+    fn collect_objects(&mut self, objects: &mut Vec<Arc<dyn Hittable>>) -> bool {
+        Self::collect_child(&mut self.left, objects);
+        if !Arc::ptr_eq(&self.left, &self.right) {
+            Self::collect_child(&mut self.right, objects);
+        }
+        true
+    }
+
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        let cost_left = self.left.sah_cost(time0, time1);
+        let cost_right = self.right.sah_cost(time0, time1);
+
+        let area = self.box_aabb.area();
+        if area <= 0. {
+            return TRAVERSAL_COST + cost_left + cost_right;
+        }
+        let area_left = self.left.bounding_box(time0, time1).unwrap().area();
+        let area_right = self.right.bounding_box(time0, time1).unwrap().area();
+
+        // 击中子节点包围盒的条件概率 = 表面积之比
+        TRAVERSAL_COST + (area_left * cost_left + area_right * cost_right) / area
+    }
+
+    fn bvh_stats(&self, depth: usize, stats: &mut BvhStats) -> bool {
+        stats.node_count += 1;
+        stats.memory += std::mem::size_of::<Self>();
+        stats.max_depth = stats.max_depth.max(depth);
+
+        let left_is_node = self.left.bvh_stats(depth + 1, stats);
+        // span == 1 时左右为同一物体, 只统计一次
+        let shared = Arc::ptr_eq(&self.left, &self.right);
+        let right_is_node = !shared && self.right.bvh_stats(depth + 1, stats);
+
+        if !left_is_node && !right_is_node {
+            stats.add_leaf(depth, if shared { 1 } else { 2 });
+        }
+        true
+    }
 }
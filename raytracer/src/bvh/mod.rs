@@ -1,2 +1,4 @@
 pub mod aabb;
+pub mod animated;
 pub mod bvh_node;
+pub mod stats;
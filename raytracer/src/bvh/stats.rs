@@ -0,0 +1,108 @@
+use std::cell::Cell;
+
+use console::style;
+
+use crate::Hit::Hittable;
+
+// ---- 求交次数计数 (每个线程各自计数, 用于热力图) ----
+// 只在开启 heatmap feature 时计数, 否则为空函数, 不影响正常渲染
+thread_local! {
+    static AABB_TESTS: Cell<u64> = Cell::new(0);
+    static PRIMITIVE_TESTS: Cell<u64> = Cell::new(0);
+}
+
+#[inline(always)]
+pub fn count_aabb_test() {
+    #[cfg(feature = "heatmap")]
+    AABB_TESTS.with(|c| c.set(c.get() + 1));
+}
+#[inline(always)]
+pub fn count_primitive_test() {
+    #[cfg(feature = "heatmap")]
+    PRIMITIVE_TESTS.with(|c| c.set(c.get() + 1));
+}
+// 返回 (AABB::hit次数, 物体求交次数) 并清零
+pub fn take_test_counts() -> (u64, u64) {
+    (
+        AABB_TESTS.with(|c| c.replace(0)),
+        PRIMITIVE_TESTS.with(|c| c.replace(0)),
+    )
+}
+
+// ---- BVH 结构统计 ----
+#[derive(Default, Debug, Clone)]
+pub struct BvhStats {
+    pub node_count: usize,      // BvhNode 数量
+    pub leaf_count: usize,      // 子节点均为物体的 BvhNode 数量
+    pub primitive_count: usize, // 物体数量
+    pub max_depth: usize,
+    pub leaf_depth_sum: usize,
+    pub leaf_sizes: Vec<usize>, // leaf_sizes[k]: 含有k个物体的叶子数量
+    pub sah_cost: f64,
+    pub memory: usize, // 节点与物体本身占用的字节数(不含贴图等共享数据)
+}
+
+impl BvhStats {
+    pub fn new<H: Hittable + ?Sized>(world: &H, time0: f64, time1: f64) -> Self {
+        let mut stats = Self::default();
+        world.bvh_stats(0, &mut stats);
+        stats.sah_cost = world.sah_cost(time0, time1);
+        stats
+    }
+
+    pub fn add_leaf(&mut self, depth: usize, size: usize) {
+        self.leaf_count += 1;
+        self.leaf_depth_sum += depth;
+        if self.leaf_sizes.len() <= size {
+            self.leaf_sizes.resize(size + 1, 0);
+        }
+        self.leaf_sizes[size] += 1;
+    }
+
+    pub fn report(&self) {
+        println!("{}", style("BVH statistics:").yellow());
+        println!(
+            "         Nodes:                     {}",
+            style(self.node_count.to_string()).yellow()
+        );
+        println!(
+            "         Leaves:                    {}",
+            style(self.leaf_count.to_string()).yellow()
+        );
+        println!(
+            "         Primitives:                {}",
+            style(self.primitive_count.to_string()).yellow()
+        );
+        println!(
+            "         Max depth:                 {}",
+            style(self.max_depth.to_string()).yellow()
+        );
+        if self.leaf_count > 0 {
+            println!(
+                "         Average leaf depth:        {}",
+                style(format!(
+                    "{:.2}",
+                    self.leaf_depth_sum as f64 / self.leaf_count as f64
+                ))
+                .yellow()
+            );
+        }
+        for (size, &count) in self.leaf_sizes.iter().enumerate() {
+            if count > 0 {
+                println!(
+                    "         Leaves of size {}:          {}",
+                    size,
+                    style(count.to_string()).yellow()
+                );
+            }
+        }
+        println!(
+            "         SAH cost:                  {}",
+            style(format!("{:.3}", self.sah_cost)).yellow()
+        );
+        println!(
+            "         Memory:                    {}",
+            style(format!("{:.2} KiB", self.memory as f64 / 1024.)).yellow()
+        );
+    }
+}
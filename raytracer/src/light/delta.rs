@@ -0,0 +1,246 @@
+use std::{f64::consts::PI, f64::INFINITY, sync::Arc};
+
+use super::ies::IesProfile;
+use crate::{
+    basic::{degree_to_radians, random_double},
+    material::ONB,
+    Hit::{Color, Point3, Vec3},
+};
+
+// 到达着色点的一束光: 指向光源的单位方向, 到光源的距离, 以及(垂直入射时的)辐照度
+pub struct LightSample {
+    pub direction: Vec3,
+    pub distance: f64,
+    pub irradiance: Color,
+}
+
+// 从光源出发的一条光线: 起点, 单位方向, 该方向上的辐射强度, 以及方向在立体角上的概率密度
+pub struct EmissionSample {
+    pub origin: Point3,
+    pub direction: Vec3,
+    pub intensity: Color,
+    pub pdf: f64,
+}
+
+// 点光源等理想光源: 光线无法击中, 只能通过光源采样(NEE)计算贡献
+pub trait DeltaLight: Send + Sync {
+    fn sample(&self, p: &Point3) -> Option<LightSample>;
+    // 采样出射光线, 用于从光源出发追踪的路径; 有配光曲线时按其重要性采样
+    fn sample_emission(&self) -> Option<EmissionSample> {
+        None
+    }
+    // 总功率, 用于按功率选择光源; 无法估计时返回None
+    fn power(&self) -> Option<f64> {
+        None
+    }
+}
+
+#[derive(Clone)]
+pub struct PointLight {
+    position: Point3,
+    intensity: Color, // 辐射强度: 距离为1处的辐照度
+    ies: Option<(Arc<IesProfile>, ONB)>,
+}
+
+impl PointLight {
+    pub fn new(position: Point3, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+            ies: None,
+        }
+    }
+    // 按配光曲线调制强度, axis 为灯具轴向
+    pub fn with_ies(mut self, profile: Arc<IesProfile>, axis: Vec3) -> Self {
+        self.ies = Some((profile, ONB::build(&axis)));
+        self
+    }
+}
+
+impl DeltaLight for PointLight {
+    fn sample(&self, p: &Point3) -> Option<LightSample> {
+        let to_light = self.position - *p;
+        let distance_squared = to_light.len_square();
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+        let profile = match &self.ies {
+            Some((profile, frame)) => profile.value(frame, &-direction),
+            None => 1.,
+        };
+        Some(LightSample {
+            direction,
+            distance,
+            irradiance: self.intensity * profile / distance_squared,
+        })
+    }
+    fn sample_emission(&self) -> Option<EmissionSample> {
+        let (direction, intensity, pdf) = match &self.ies {
+            Some((profile, frame)) => {
+                let (direction, pdf) = profile.sample(frame, random_double(), random_double());
+                (direction, profile.value(frame, &direction), pdf)
+            }
+            // 整个球面上均匀采样
+            None => {
+                let z = 1. - 2. * random_double();
+                let phi = 2. * PI * random_double();
+                let r = (1. - z * z).max(0.).sqrt();
+                (
+                    Vec3::new(r * phi.cos(), r * phi.sin(), z),
+                    1.,
+                    1. / (4. * PI),
+                )
+            }
+        };
+        if pdf <= 0. {
+            return None;
+        }
+        Some(EmissionSample {
+            origin: self.position,
+            direction,
+            intensity: self.intensity * intensity,
+            pdf,
+        })
+    }
+    fn power(&self) -> Option<f64> {
+        let solid_angle = match &self.ies {
+            Some((profile, _)) => profile.integral(),
+            None => 4. * PI,
+        };
+        Some(solid_angle * self.intensity.luminance())
+    }
+}
+
+// 聚光灯: falloff_start 以内为全强度, 到 total_width 处平滑衰减为0(均为半角, 角度)
+#[derive(Clone)]
+pub struct SpotLight {
+    position: Point3,
+    direction: Vec3,
+    intensity: Color,
+    cos_total_width: f64,
+    cos_falloff_start: f64,
+    ies: Option<(Arc<IesProfile>, ONB)>,
+    solid_angle: f64, // 按相对强度加权的发光立体角, 用于估计功率
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Point3,
+        direction: Vec3,
+        intensity: Color,
+        total_width: f64,
+        falloff_start: f64,
+    ) -> Self {
+        let cos_total_width = degree_to_radians(total_width).cos();
+        let cos_falloff_start = degree_to_radians(falloff_start.min(total_width)).cos();
+        // 衰减部分近似按一半计
+        let cos_average = 0.5 * (cos_falloff_start + cos_total_width);
+        Self {
+            position,
+            direction: direction.unit_vector(),
+            intensity,
+            cos_total_width,
+            cos_falloff_start,
+            ies: None,
+            solid_angle: 2. * PI * (1. - cos_average),
+        }
+    }
+    // 按配光曲线调制强度, 灯具轴向即聚光方向
+    pub fn with_ies(mut self, profile: Arc<IesProfile>) -> Self {
+        self.solid_angle = profile.falloff_integral(|cos_theta| self.falloff(cos_theta));
+        self.ies = Some((profile, ONB::build(&self.direction)));
+        self
+    }
+
+    fn falloff(&self, cos_theta: f64) -> f64 {
+        if cos_theta >= self.cos_falloff_start {
+            return 1.;
+        }
+        if cos_theta <= self.cos_total_width {
+            return 0.;
+        }
+        // smoothstep
+        let x =
+            (cos_theta - self.cos_total_width) / (self.cos_falloff_start - self.cos_total_width);
+        x * x * (3. - 2. * x)
+    }
+}
+
+impl DeltaLight for SpotLight {
+    fn sample(&self, p: &Point3) -> Option<LightSample> {
+        let to_light = self.position - *p;
+        let distance_squared = to_light.len_square();
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+
+        let mut falloff = self.falloff(Vec3::dot(&-direction, &self.direction));
+        if let Some((profile, frame)) = &self.ies {
+            falloff *= profile.value(frame, &-direction);
+        }
+        if falloff <= 0. {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance,
+            irradiance: self.intensity * falloff / distance_squared,
+        })
+    }
+    fn sample_emission(&self) -> Option<EmissionSample> {
+        let (direction, pdf) = match &self.ies {
+            Some((profile, frame)) => profile.sample(frame, random_double(), random_double()),
+            // 在 total_width 的圆锥内均匀采样
+            None => {
+                let z = 1. - random_double() * (1. - self.cos_total_width);
+                let phi = 2. * PI * random_double();
+                let r = (1. - z * z).max(0.).sqrt();
+                let local = Vec3::new(r * phi.cos(), r * phi.sin(), z);
+                (
+                    ONB::build(&self.direction).local(local),
+                    1. / (2. * PI * (1. - self.cos_total_width)),
+                )
+            }
+        };
+        let mut falloff = self.falloff(Vec3::dot(&direction, &self.direction));
+        if let Some((profile, frame)) = &self.ies {
+            falloff *= profile.value(frame, &direction);
+        }
+        if pdf <= 0. || falloff <= 0. {
+            return None;
+        }
+        Some(EmissionSample {
+            origin: self.position,
+            direction,
+            intensity: self.intensity * falloff,
+            pdf,
+        })
+    }
+    fn power(&self) -> Option<f64> {
+        Some(self.solid_angle * self.intensity.luminance())
+    }
+}
+
+// 平行光(如太阳): 位于无穷远处, 各处辐照度相同
+#[derive(Clone, Copy)]
+pub struct DirectionalLight {
+    direction: Vec3, // 光的传播方向
+    irradiance: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, irradiance: Color) -> Self {
+        Self {
+            direction: direction.unit_vector(),
+            irradiance,
+        }
+    }
+}
+
+impl DeltaLight for DirectionalLight {
+    fn sample(&self, _p: &Point3) -> Option<LightSample> {
+        Some(LightSample {
+            direction: -self.direction,
+            distance: INFINITY,
+            irradiance: self.irradiance,
+        })
+    }
+}
@@ -0,0 +1,190 @@
+// 分段常数分布的逆变换采样, 用于按亮度对环境贴图等做重要性采样
+
+pub struct Distribution1D {
+    func: Vec<f64>,
+    cdf: Vec<f64>, // cdf[0] = 0, cdf[n] = 1
+    integral: f64, // func 在 [0, 1] 上的积分
+}
+
+impl Distribution1D {
+    pub fn new(func: Vec<f64>) -> Self {
+        let n = func.len();
+        let mut cdf = vec![0.; n + 1];
+        for i in 0..n {
+            cdf[i + 1] = cdf[i] + func[i] / n as f64;
+        }
+        let integral = cdf[n];
+        for (i, c) in cdf.iter_mut().enumerate() {
+            // 全为0时退化为均匀分布
+            *c = if integral > 0. {
+                *c / integral
+            } else {
+                i as f64 / n as f64
+            };
+        }
+
+        Self {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.func.len()
+    }
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    // 返回 [0, 1) 中的采样点, 该点的概率密度, 以及所在的段
+    pub fn sample_continuous(&self, u: f64) -> (f64, f64, usize) {
+        let n = self.count();
+        // 最后一个满足 cdf[i] <= u 的段
+        let offset = match self.cdf.binary_search_by(|c| c.partial_cmp(&u).unwrap()) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        }
+        .min(n - 1);
+
+        let width = self.cdf[offset + 1] - self.cdf[offset];
+        let du = if width > 0. {
+            (u - self.cdf[offset]) / width
+        } else {
+            0.
+        };
+
+        (
+            (offset as f64 + du) / n as f64,
+            self.density(offset),
+            offset,
+        )
+    }
+
+    fn density(&self, offset: usize) -> f64 {
+        if self.integral > 0. {
+            self.func[offset] / self.integral
+        } else {
+            1.
+        }
+    }
+
+    // x \in [0, 1) 处的概率密度
+    pub fn pdf(&self, x: f64) -> f64 {
+        let offset = ((x * self.count() as f64) as usize).min(self.count() - 1);
+        self.density(offset)
+    }
+}
+
+// 二维: 先按行的边缘分布采样 v, 再按该行的条件分布采样 u
+pub struct Distribution2D {
+    conditional: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    // func: nv 行, 每行 nu 个值
+    pub fn new(func: &[f64], nu: usize, nv: usize) -> Self {
+        let conditional: Vec<Distribution1D> = (0..nv)
+            .map(|v| Distribution1D::new(func[v * nu..(v + 1) * nu].to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(conditional.iter().map(|c| c.integral()).collect());
+        Self {
+            conditional,
+            marginal,
+        }
+    }
+
+    // func 在 [0, 1]^2 上的积分
+    pub fn integral(&self) -> f64 {
+        self.marginal.integral()
+    }
+
+    // 返回 (u, v) \in [0, 1)^2 以及其概率密度
+    pub fn sample_continuous(&self, u1: f64, u2: f64) -> ((f64, f64), f64) {
+        let (v, pdf_v, offset) = self.marginal.sample_continuous(u2);
+        let (u, pdf_u, _) = self.conditional[offset].sample_continuous(u1);
+        ((u, v), pdf_u * pdf_v)
+    }
+
+    pub fn pdf(&self, u: f64, v: f64) -> f64 {
+        let nv = self.marginal.count();
+        let row = ((v * nv as f64) as usize).min(nv - 1);
+        self.conditional[row].pdf(u) * self.marginal.pdf(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cdf_inversion() {
+        // 积分 = (1 + 3 + 0 + 4) / 4 = 2
+        let d = Distribution1D::new(vec![1., 3., 0., 4.]);
+        assert_eq!(d.count(), 4);
+        assert!((d.integral() - 2.).abs() < 1e-12);
+
+        // cdf = [0, 1/8, 1/2, 1/2, 1]
+        let (x, pdf, offset) = d.sample_continuous(0.);
+        assert_eq!((x, offset), (0., 0));
+        assert!((pdf - 0.5).abs() < 1e-12);
+        let (x, pdf, offset) = d.sample_continuous(0.3);
+        assert_eq!(offset, 1);
+        assert!((x - (1. + 0.175 / 0.375) / 4.).abs() < 1e-12);
+        assert!((pdf - 1.5).abs() < 1e-12);
+        // 值为 0 的段不会被采到
+        let (x, pdf, offset) = d.sample_continuous(0.5);
+        assert_eq!(offset, 3);
+        assert!((x - 0.75).abs() < 1e-12);
+        assert!((pdf - 2.).abs() < 1e-12);
+        let (x, _, offset) = d.sample_continuous(1. - 1e-12);
+        assert_eq!(offset, 3);
+        assert!(x < 1.);
+    }
+
+    #[test]
+    fn pdf_matches_sample() {
+        let func = vec![0.2, 5., 1., 0., 2.5, 0.7];
+        let d = Distribution1D::new(func.clone());
+        let mut total = 0.;
+        for i in 0..1000 {
+            let (x, pdf, offset) = d.sample_continuous((i as f64 + 0.5) / 1000.);
+            assert!((d.pdf(x) - pdf).abs() < 1e-12);
+            assert!(pdf > 0.);
+            total += func[offset] / pdf;
+        }
+        // E[f / pdf] = 积分
+        assert!((total / 1000. - d.integral()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn all_zero_is_uniform() {
+        let d = Distribution1D::new(vec![0.; 5]);
+        assert_eq!(d.integral(), 0.);
+        for &u in [0., 0.1, 0.45, 0.99].iter() {
+            let (x, pdf, _) = d.sample_continuous(u);
+            assert!((x - u).abs() < 1e-12);
+            assert_eq!(pdf, 1.);
+            assert_eq!(d.pdf(x), 1.);
+        }
+    }
+
+    #[test]
+    fn distribution_2d() {
+        // 2 行 3 列
+        let func = [1., 2., 3., 0., 0., 6.];
+        let d = Distribution2D::new(&func, 3, 2);
+        assert!((d.integral() - 2.).abs() < 1e-12);
+        for i in 0..50 {
+            for j in 0..50 {
+                let ((u, v), pdf) =
+                    d.sample_continuous((i as f64 + 0.5) / 50., (j as f64 + 0.5) / 50.);
+                assert!((d.pdf(u, v) - pdf).abs() < 1e-12);
+                // 采样点处的 func / pdf 恒等于积分
+                let value = func[(v * 2.) as usize * 3 + (u * 3.) as usize];
+                assert!((value / pdf - d.integral()).abs() < 1e-9);
+            }
+        }
+    }
+}
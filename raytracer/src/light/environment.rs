@@ -0,0 +1,183 @@
+use std::{f64::consts::PI, f64::INFINITY, fs::File, io::BufReader};
+
+use image::codecs::hdr::HdrDecoder;
+
+use super::{distribution::Distribution2D, Background};
+use crate::{
+    basic::{degree_to_radians, random_double},
+    bvh::aabb::AABB,
+    material::{Material, ScatterRecord},
+    Hit::{Color, HitRecord, Hittable, Point3, Ray, Vec3},
+};
+
+// 等距柱状投影(equirectangular)的环境贴图, 既作为背景, 加入 lights 后也可作为光源被采样
+// 高动态范围只支持 Radiance .hdr; 其他格式按 LDR 图片读入(16 位图片保留精度, 不做 gamma 变换, 与 ImageTexture 一致)
+// 当前的 image 版本不能解码 OpenEXR, .exr 会返回错误, 需要先转换为 .hdr
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    data: Vec<Color>, // 从上到下逐行
+    sin_theta: f64,   // 绕 y 轴旋转的角度
+    cos_theta: f64,
+    intensity: f64,
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    // 文件不存在, 格式不支持或解码失败时返回带文件名的错误信息
+    pub fn new(filename: &str) -> Result<Self, String> {
+        let lower = filename.to_lowercase();
+        if lower.ends_with(".exr") {
+            return Err(format!(
+                "OpenEXR environment maps are not supported, convert \"{}\" to Radiance .hdr",
+                filename
+            ));
+        }
+        if lower.ends_with(".hdr") {
+            let file = File::open(filename)
+                .map_err(|e| format!("failed to open environment map \"{}\": {}", filename, e))?;
+            let decoder = HdrDecoder::new(BufReader::new(file))
+                .map_err(|e| format!("invalid Radiance .hdr file \"{}\": {}", filename, e))?;
+            let meta = decoder.metadata();
+            let data = decoder
+                .read_image_hdr()
+                .map_err(|e| format!("failed to decode \"{}\": {}", filename, e))?
+                .iter()
+                .map(|p| Color::new(p[0] as f64, p[1] as f64, p[2] as f64))
+                .collect();
+            Ok(Self::from_pixels(
+                meta.width as usize,
+                meta.height as usize,
+                data,
+            ))
+        } else {
+            let image = image::open(filename)
+                .map_err(|e| format!("failed to load environment map \"{}\": {}", filename, e))?
+                .into_rgb16();
+            let color_scale = 1. / 65535.;
+            let data = image
+                .pixels()
+                .map(|p| Color::new(p[0] as f64, p[1] as f64, p[2] as f64) * color_scale)
+                .collect();
+            Ok(Self::from_pixels(
+                image.width() as usize,
+                image.height() as usize,
+                data,
+            ))
+        }
+    }
+
+    pub fn from_pixels(width: usize, height: usize, data: Vec<Color>) -> Self {
+        // 按亮度 * sin(theta) 采样: 靠近两极的像素对应的立体角更小
+        let mut func = Vec::with_capacity(width * height);
+        for j in 0..height {
+            let sin_theta = (PI * (j as f64 + 0.5) / height as f64).sin();
+            for i in 0..width {
+                func.push(data[j * width + i].luminance() * sin_theta);
+            }
+        }
+        let distribution = Distribution2D::new(&func, width, height);
+
+        Self {
+            width,
+            height,
+            data,
+            sin_theta: 0.,
+            cos_theta: 1.,
+            intensity: 1.,
+            distribution,
+        }
+    }
+
+    pub fn with_rotation(mut self, angle: f64) -> Self {
+        let radians = degree_to_radians(angle);
+        self.sin_theta = radians.sin();
+        self.cos_theta = radians.cos();
+        self
+    }
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    // 世界坐标中的方向 -> 贴图坐标 (u, s), s 从上往下, 与 Sphere 的 uv 一致(v = 1 - s)
+    fn direction_to_uv(&self, dir: &Vec3) -> (f64, f64) {
+        let d = dir.unit_vector();
+        let x = self.cos_theta * d.x() - self.sin_theta * d.z();
+        let z = self.sin_theta * d.x() + self.cos_theta * d.z();
+        let theta = (-d.y()).clamp(-1., 1.).acos();
+        let phi = x.atan2(-z) + PI;
+        (phi / (2. * PI), 1. - theta / PI)
+    }
+    fn uv_to_direction(&self, u: f64, s: f64) -> Vec3 {
+        let theta = (1. - s) * PI;
+        let phi = 2. * PI * u - PI;
+        let (x, y, z) = (
+            theta.sin() * phi.sin(),
+            -theta.cos(),
+            -theta.sin() * phi.cos(),
+        );
+        Vec3::new(
+            self.cos_theta * x + self.sin_theta * z,
+            y,
+            -self.sin_theta * x + self.cos_theta * z,
+        )
+    }
+
+    fn lookup(&self, u: f64, s: f64) -> Color {
+        let i = ((u * self.width as f64) as usize).min(self.width - 1);
+        let j = ((s * self.height as f64) as usize).min(self.height - 1);
+        self.data[j * self.width + i] * self.intensity
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn value(&self, direction: &Vec3) -> Color {
+        let (u, s) = self.direction_to_uv(direction);
+        self.lookup(u, s)
+    }
+}
+
+// 作为光源时的"材质": 贴图坐标存放在 HitRecord 的 (u, v) 中
+impl Material for EnvironmentMap {
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord) -> Option<ScatterRecord> {
+        None
+    }
+    fn emitted(&self, u: f64, v: f64, _p: &Point3) -> Option<Color> {
+        Some(self.lookup(u, v))
+    }
+}
+
+impl Hittable for EnvironmentMap {
+    // 位于无穷远处, 只有不限距离的光线才能击中
+    fn hit(&self, r: &Ray, _t_min: f64, t_max: f64) -> Option<HitRecord> {
+        if t_max < INFINITY {
+            return None;
+        }
+        let dir = r.direction().unit_vector();
+        let (u, s) = self.direction_to_uv(&dir);
+        Some(HitRecord::new(f64::MAX, dir, -dir, true, self, u, s))
+    }
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        None
+    }
+
+    // 立体角上的密度: dw = 2 pi^2 sin(theta) du ds
+    fn pdf_value(&self, _o: &Point3, v: &Vec3) -> f64 {
+        let (u, s) = self.direction_to_uv(v);
+        let sin_theta = (PI * s).sin();
+        if sin_theta <= 0. {
+            return 0.;
+        }
+        self.distribution.pdf(u, s) / (2. * PI * PI * sin_theta)
+    }
+    fn random(&self, _o: &Vec3) -> Vec3 {
+        let ((u, s), _) = self
+            .distribution
+            .sample_continuous(random_double(), random_double());
+        self.uv_to_direction(u, s)
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+}
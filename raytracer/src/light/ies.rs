@@ -0,0 +1,359 @@
+use std::{f64::consts::PI, fs};
+
+use super::distribution::Distribution2D;
+use crate::{material::ONB, Hit::Vec3};
+
+// 对配光曲线积分与采样时 (theta, phi) 网格的分辨率
+const GRID_THETA: usize = 64;
+const GRID_PHI: usize = 128;
+
+// IES LM-63 配光曲线(只支持 C 类光度数据)
+// 灯具坐标系: 竖直角 0 度为灯具的轴向(一般朝下), 水平角 0 度为 frame.u() 方向
+// 强度按最大值归一化, 光源的强度即为峰值强度
+// 出射方向按 (theta, phi) 网格上的 相对强度 * 立体角 做重要性采样
+pub struct IesProfile {
+    vertical_angles: Vec<f64>,    // 角度, 递增
+    horizontal_angles: Vec<f64>,  // 角度, 递增
+    candela: Vec<Vec<f64>>,       // [水平角][竖直角]
+    distribution: Distribution2D, // GRID_THETA 行(theta), 每行 GRID_PHI 个(phi)
+}
+
+impl IesProfile {
+    pub fn from_file(filename: &str) -> Self {
+        let text = fs::read_to_string(filename).expect("failed to read IES file");
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut lines = text.lines();
+        // 跳过文件头与关键字, 直到 TILT=
+        let tilt = lines
+            .by_ref()
+            .map(|line| line.trim())
+            .find(|line| line.starts_with("TILT="))
+            .expect("IES file has no TILT line");
+
+        let mut numbers = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<f64>().expect("invalid number in IES file"));
+        let mut next = || numbers.next().expect("unexpected end of IES file");
+
+        // 倾斜数据不影响配光, 只跳过
+        if tilt == "TILT=INCLUDE" {
+            next(); // lamp-to-luminaire geometry
+            let pairs = next() as usize;
+            for _ in 0..2 * pairs {
+                next();
+            }
+        }
+
+        // <灯数> <每灯流明> <倍率> <竖直角数> <水平角数> <光度类型> <单位> <宽> <长> <高>
+        next();
+        next();
+        let multiplier = next();
+        let vertical_count = next() as usize;
+        let horizontal_count = next() as usize;
+        // 1: C 类, 2: B 类, 3: A 类; B/A 类的角度定义不同, 按 C 类解释会得到错误的配光
+        let photometric_type = next();
+        assert!(
+            photometric_type == 1.,
+            "unsupported IES photometric type {} (only type C is supported)",
+            photometric_type
+        );
+        for _ in 0..4 {
+            next();
+        }
+        // <镇流器系数> <未使用> <输入功率>
+        for _ in 0..3 {
+            next();
+        }
+
+        let vertical_angles: Vec<f64> = (0..vertical_count).map(|_| next()).collect();
+        let horizontal_angles: Vec<f64> = (0..horizontal_count).map(|_| next()).collect();
+        let mut candela: Vec<Vec<f64>> = (0..horizontal_count)
+            .map(|_| (0..vertical_count).map(|_| next() * multiplier).collect())
+            .collect();
+
+        let max = candela.iter().flatten().cloned().fold(0., f64::max);
+        if max > 0. {
+            for c in candela.iter_mut().flatten() {
+                *c /= max;
+            }
+        }
+
+        Self::new(vertical_angles, horizontal_angles, candela)
+    }
+
+    pub fn new(
+        vertical_angles: Vec<f64>,
+        horizontal_angles: Vec<f64>,
+        candela: Vec<Vec<f64>>,
+    ) -> Self {
+        let mut profile = Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+            distribution: Distribution2D::new(&[0.], 1, 1),
+        };
+        let mut func = Vec::with_capacity(GRID_THETA * GRID_PHI);
+        for j in 0..GRID_THETA {
+            let theta = (j as f64 + 0.5) * PI / GRID_THETA as f64;
+            for i in 0..GRID_PHI {
+                let phi = (i as f64 + 0.5) * 2. * PI / GRID_PHI as f64;
+                func.push(profile.intensity(theta.to_degrees(), phi.to_degrees()) * theta.sin());
+            }
+        }
+        profile.distribution = Distribution2D::new(&func, GRID_PHI, GRID_THETA);
+        profile
+    }
+
+    // 在整个球面上对 相对强度 * weight(theta) 积分, theta 为与灯具轴向的夹角(弧度)
+    fn integrate<F: Fn(f64) -> f64>(&self, weight: F) -> f64 {
+        let d_theta = PI / GRID_THETA as f64;
+        let d_phi = 2. * PI / GRID_PHI as f64;
+        let mut sum = 0.;
+        for j in 0..GRID_THETA {
+            let theta = (j as f64 + 0.5) * d_theta;
+            let w = weight(theta) * theta.sin();
+            if w == 0. {
+                continue;
+            }
+            for i in 0..GRID_PHI {
+                let phi = (i as f64 + 0.5) * d_phi;
+                sum += self.intensity(theta.to_degrees(), phi.to_degrees()) * w;
+            }
+        }
+        sum * d_theta * d_phi
+    }
+
+    // 在 angles 中定位 x, 返回下标与插值系数; 超出范围时返回 None
+    fn locate(angles: &[f64], x: f64) -> Option<(usize, f64)> {
+        let (first, last) = (angles[0], angles[angles.len() - 1]);
+        if x < first || x > last {
+            return None;
+        }
+        if angles.len() == 1 {
+            return Some((0, 0.));
+        }
+        let i = angles
+            .iter()
+            .rposition(|&a| a <= x)
+            .unwrap()
+            .min(angles.len() - 2);
+        let width = angles[i + 1] - angles[i];
+        let t = if width > 0. {
+            (x - angles[i]) / width
+        } else {
+            0.
+        };
+        Some((i, t))
+    }
+
+    // theta: 竖直角, phi: 水平角 [0, 360), 均为角度
+    fn intensity(&self, theta: f64, phi: f64) -> f64 {
+        let (v, tv) = match Self::locate(&self.vertical_angles, theta) {
+            Some(v) => v,
+            None => return 0.,
+        };
+
+        // 按水平角的范围判断对称性
+        let last = self.horizontal_angles[self.horizontal_angles.len() - 1];
+        let phi = if last <= 0. {
+            0.
+        } else if last <= 90. {
+            let phi = phi % 180.;
+            if phi > 90. {
+                180. - phi
+            } else {
+                phi
+            }
+        } else if last <= 180. {
+            if phi > 180. {
+                360. - phi
+            } else {
+                phi
+            }
+        } else {
+            phi
+        };
+        let (h, th) = Self::locate(&self.horizontal_angles, phi).unwrap_or((0, 0.));
+
+        let at = |h: usize, v: usize| {
+            let h = h.min(self.horizontal_angles.len() - 1);
+            let v = v.min(self.vertical_angles.len() - 1);
+            self.candela[h][v]
+        };
+        let lower = at(h, v) * (1. - tv) + at(h, v + 1) * tv;
+        let upper = at(h + 1, v) * (1. - tv) + at(h + 1, v + 1) * tv;
+        lower * (1. - th) + upper * th
+    }
+
+    // 灯具坐标系中的 (theta, phi), 均为弧度, phi \in [0, 2 pi)
+    fn angles(frame: &ONB, dir: &Vec3) -> (f64, f64) {
+        let d = dir.unit_vector();
+        let x = Vec3::dot(&d, &frame.u());
+        let y = Vec3::dot(&d, &frame.v());
+        let z = Vec3::dot(&d, &frame.w());
+        let phi = y.atan2(x);
+        (
+            z.clamp(-1., 1.).acos(),
+            if phi < 0. { phi + 2. * PI } else { phi },
+        )
+    }
+
+    // frame.w() 为灯具轴向(竖直角 0 度), 返回方向 dir 上的相对强度
+    pub fn value(&self, frame: &ONB, dir: &Vec3) -> f64 {
+        let (theta, phi) = Self::angles(frame, dir);
+        self.intensity(theta.to_degrees(), phi.to_degrees())
+    }
+
+    // 按相对强度采样出射方向, 返回单位方向及其立体角上的概率密度
+    // 网格上 du dv 对应的立体角为 2 pi^2 sin(theta)
+    pub fn sample(&self, frame: &ONB, u1: f64, u2: f64) -> (Vec3, f64) {
+        let ((u, v), pdf) = self.distribution.sample_continuous(u1, u2);
+        let theta = v * PI;
+        let phi = u * 2. * PI;
+        let sin_theta = theta.sin();
+        let dir = frame.local(Vec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            theta.cos(),
+        ));
+        if sin_theta <= 0. {
+            return (dir, 0.);
+        }
+        (dir, pdf / (2. * PI * PI * sin_theta))
+    }
+    pub fn pdf(&self, frame: &ONB, dir: &Vec3) -> f64 {
+        let (theta, phi) = Self::angles(frame, dir);
+        let sin_theta = theta.sin();
+        if sin_theta <= 0. {
+            return 0.;
+        }
+        self.distribution.pdf(phi / (2. * PI), theta / PI) / (2. * PI * PI * sin_theta)
+    }
+
+    // 在单位立体角上对相对强度积分, 即光通量与峰值强度之比
+    pub fn integral(&self) -> f64 {
+        self.distribution.integral() * 2. * PI * PI
+    }
+    // 在轴向一侧的半球上对 相对强度 * cos 积分; 用于面光源, Lambert 发光时为 PI
+    pub fn cosine_integral(&self) -> f64 {
+        self.integrate(|theta| theta.cos().max(0.))
+    }
+    // 聚光灯: 对 相对强度 * 聚光衰减 积分
+    pub fn falloff_integral<F: Fn(f64) -> f64>(&self, falloff: F) -> f64 {
+        self.integrate(|theta| falloff(theta.cos()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 水平角 0 ~ 90 度: 四象限对称
+    const QUADRANT: &str = "IESNA:LM-63-2002
+[TEST] quadrant symmetric
+[MANUFAC] none
+TILT=NONE
+1 1000 2 3 2 1 1 0 0 0
+1.0 1 100
+0 45 90
+0 90
+100 80 20
+50, 40, 10
+";
+
+    fn axis_frame() -> ONB {
+        ONB::build(&Vec3::new(0., 0., 1.))
+    }
+
+    #[test]
+    fn parse_and_normalize() {
+        let profile = IesProfile::parse(QUADRANT);
+        assert_eq!(profile.vertical_angles, vec![0., 45., 90.]);
+        assert_eq!(profile.horizontal_angles, vec![0., 90.]);
+        // 乘以倍率后按最大值(200)归一化
+        assert_eq!(
+            profile.candela,
+            vec![vec![1., 0.8, 0.2], vec![0.5, 0.4, 0.1]]
+        );
+    }
+
+    #[test]
+    fn tilt_include_is_skipped() {
+        let text = QUADRANT.replace("TILT=NONE", "TILT=INCLUDE\n1\n2\n0 90\n1 1");
+        assert_eq!(
+            IesProfile::parse(&text).candela,
+            IesProfile::parse(QUADRANT).candela
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "photometric type")]
+    fn type_b_is_rejected() {
+        IesProfile::parse(&QUADRANT.replace("1 1000 2 3 2 1 1", "1 1000 2 3 2 2 1"));
+    }
+
+    #[test]
+    fn interpolation_and_symmetry() {
+        let profile = IesProfile::parse(QUADRANT);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+        assert!(close(profile.intensity(0., 0.), 1.));
+        assert!(close(profile.intensity(45., 90.), 0.4));
+        assert!(close(profile.intensity(22.5, 0.), 0.9));
+        assert!(close(profile.intensity(45., 45.), 0.6));
+        // 四象限对称
+        assert!(close(profile.intensity(45., 180.), 0.8));
+        assert!(close(profile.intensity(45., 270.), 0.4));
+        assert!(close(profile.intensity(45., 315.), 0.6));
+        // 超出竖直角范围
+        assert_eq!(profile.intensity(120., 0.), 0.);
+
+        // 只有一个水平角时旋转对称
+        let text = QUADRANT
+            .replace("3 2 1 1", "3 1 1 1")
+            .replace("0 90\n100", "0\n100")
+            .replace("50, 40, 10\n", "");
+        let profile = IesProfile::parse(&text);
+        assert!(close(
+            profile.intensity(45., 0.),
+            profile.intensity(45., 123.)
+        ));
+    }
+
+    #[test]
+    fn value_uses_frame() {
+        let profile = IesProfile::parse(QUADRANT);
+        let frame = axis_frame();
+        assert!((profile.value(&frame, &frame.w()) - 1.).abs() < 1e-12);
+        assert!((profile.value(&frame, &(frame.w() + frame.v())) - 0.4).abs() < 1e-12);
+        assert_eq!(profile.value(&frame, &-frame.w()), 0.);
+    }
+
+    #[test]
+    fn sample_matches_pdf_and_integral() {
+        let profile = IesProfile::parse(QUADRANT);
+        let frame = axis_frame();
+        let n = 400;
+        let mut estimate = 0.;
+        for i in 0..n {
+            for j in 0..n {
+                let (dir, pdf) = profile.sample(
+                    &frame,
+                    (i as f64 + 0.5) / n as f64,
+                    (j as f64 + 0.5) / n as f64,
+                );
+                assert!((dir.len() - 1.).abs() < 1e-9);
+                // 只会采到有光的方向
+                assert!(Vec3::dot(&dir, &frame.w()) >= -1e-9);
+                assert!(pdf > 0.);
+                assert!((profile.pdf(&frame, &dir) - pdf).abs() < 1e-6 * pdf);
+                estimate += profile.value(&frame, &dir) / pdf;
+            }
+        }
+        let estimate = estimate / (n * n) as f64;
+        assert!((estimate - profile.integral()).abs() < 0.01 * profile.integral());
+    }
+}
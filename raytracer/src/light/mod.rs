@@ -0,0 +1,123 @@
+pub mod delta;
+pub mod distribution;
+pub mod environment;
+pub mod ies;
+pub mod sampler;
+pub mod sky;
+
+use std::sync::Arc;
+
+use crate::{
+    basic::VEC3::{Color, Point3, Vec3},
+    material::Material,
+    Hit::{Hittable, HittableList},
+};
+use delta::DeltaLight;
+use sampler::AliasTable;
+
+// 光线没有击中任何物体时, 沿 direction 方向看到的辐射亮度
+pub trait Background: Send + Sync {
+    fn value(&self, direction: &Vec3) -> Color;
+}
+
+// 纯色背景
+impl Background for Color {
+    fn value(&self, _direction: &Vec3) -> Color {
+        *self
+    }
+}
+
+// 面光源的功率估计: 以 p 处的发光亮度代表整个表面, 方向分布由材质给出(如配光曲线)
+pub fn area_power(mat: &dyn Material, p: &Point3, area: f64) -> Option<f64> {
+    let emitted = mat.emitted(0.5, 0.5, p)?;
+    Some(mat.emitted_cosine_integral() * emitted.luminance() * area)
+}
+
+// 场景中的全部光源, 按功率选择光源
+#[derive(Default, Clone)]
+pub struct Lights {
+    area: HittableList, // 可被光线击中的光源(同时需要加入world), 与材质采样做MIS
+    delta: Vec<Arc<dyn DeltaLight>>, // 点光源等, 只能通过光源采样计算
+    table: AliasTable,  // 下标先为面光源, 后为理想光源
+}
+
+pub enum LightChoice<'a> {
+    Area(&'a dyn Hittable),
+    Delta(&'a dyn DeltaLight),
+}
+
+impl Lights {
+    pub fn new(area: HittableList) -> Self {
+        // 不能采样的物体(介质, SDF, CSG 等)注册为光源时直接报错, 而不是渲染出错误的结果
+        for (i, light) in area.objects.iter().enumerate() {
+            assert!(
+                light.light_samplable(),
+                "area light #{} does not support light sampling (pdf_value/random)",
+                i
+            );
+        }
+        let mut lights = Self {
+            area,
+            delta: Vec::new(),
+            table: AliasTable::default(),
+        };
+        lights.build();
+        lights
+    }
+    pub fn add_delta(&mut self, light: Arc<dyn DeltaLight>) {
+        self.delta.push(light);
+        self.build();
+    }
+
+    pub fn area(&self) -> &HittableList {
+        &self.area
+    }
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    // 无法估计功率的光源按其余光源的平均功率计
+    fn build(&mut self) {
+        let powers: Vec<Option<f64>> = self
+            .area
+            .objects
+            .iter()
+            .map(|light| light.power())
+            .chain(self.delta.iter().map(|light| light.power()))
+            .collect();
+        let known: Vec<f64> = powers.iter().flatten().copied().collect();
+        let average = if known.is_empty() {
+            1.
+        } else {
+            known.iter().sum::<f64>() / known.len() as f64
+        };
+        let weights: Vec<f64> = powers.iter().map(|p| p.unwrap_or(average)).collect();
+        self.table = AliasTable::new(&weights);
+    }
+
+    // 选择一个光源, 返回其被选中的概率
+    pub fn choose(&self) -> Option<(LightChoice, f64)> {
+        if self.is_empty() {
+            return None;
+        }
+        let (index, pmf) = self.table.sample();
+        let area_count = self.area.objects.len();
+        let light = if index < area_count {
+            LightChoice::Area(&*self.area.objects[index])
+        } else {
+            LightChoice::Delta(&*self.delta[index - area_count])
+        };
+        Some((light, pmf))
+    }
+
+    // 光源采样得到方向 v 的概率密度(各面光源的密度按选中概率加权)
+    // 需要对每个光源求值, 大量小光源(如网格的发光三角形)应先合并为一个 BvhNode
+    pub fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        self.area
+            .objects
+            .iter()
+            .enumerate()
+            .map(|(i, light)| self.table.pmf(i) * light.pdf_value(o, v))
+            .sum()
+    }
+}
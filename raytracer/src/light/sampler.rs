@@ -0,0 +1,120 @@
+use crate::basic::random_double;
+
+// Walker/Vose 别名表: O(1) 按权重采样离散分布
+// reference: https://www.keithschwarz.com/darts-dice-coins/
+#[derive(Default, Clone)]
+pub struct AliasTable {
+    prob: Vec<f64>,
+    alias: Vec<usize>,
+    pmf: Vec<f64>,
+}
+
+impl AliasTable {
+    pub fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let sum: f64 = weights.iter().sum();
+        // 权重全为0时退化为均匀分布
+        let pmf: Vec<f64> = if sum > 0. {
+            weights.iter().map(|w| w / sum).collect()
+        } else {
+            vec![1. / n as f64; n]
+        };
+
+        let mut prob: Vec<f64> = pmf.iter().map(|p| p * n as f64).collect();
+        let mut alias: Vec<usize> = (0..n).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) = (0..n).partition(|&i| prob[i] < 1.);
+
+        while let (Some(&s), Some(&l)) = (small.last(), large.last()) {
+            small.pop();
+            // s 剩余的部分由 l 补足
+            alias[s] = l;
+            prob[l] -= 1. - prob[s];
+            if prob[l] < 1. {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // 浮点误差导致的剩余项都视为满的
+        for i in small.into_iter().chain(large) {
+            prob[i] = 1.;
+        }
+
+        Self { prob, alias, pmf }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pmf.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.pmf.is_empty()
+    }
+    pub fn pmf(&self, i: usize) -> f64 {
+        self.pmf[i]
+    }
+
+    // 返回选中的下标及其概率
+    pub fn sample(&self) -> (usize, f64) {
+        let n = self.len();
+        let u = random_double() * n as f64;
+        let i = (u as usize).min(n - 1);
+        let index = if u - (i as f64) < self.prob[i] {
+            i
+        } else {
+            self.alias[i]
+        };
+        (index, self.pmf[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 由别名表反推出的每一项的概率
+    fn table_pmf(table: &AliasTable) -> Vec<f64> {
+        let n = table.len();
+        let mut p = vec![0.; n];
+        for i in 0..n {
+            p[i] += table.prob[i] / n as f64;
+            p[table.alias[i]] += (1. - table.prob[i]) / n as f64;
+        }
+        p
+    }
+
+    #[test]
+    fn table_matches_weights() {
+        let weights = [1., 7., 0., 2., 0.5, 4.5];
+        let table = AliasTable::new(&weights);
+        assert_eq!(table.len(), 6);
+        for (i, p) in table_pmf(&table).iter().enumerate() {
+            assert!((table.pmf(i) - weights[i] / 15.).abs() < 1e-12);
+            assert!((p - table.pmf(i)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn all_zero_is_uniform() {
+        let table = AliasTable::new(&[0.; 4]);
+        for p in table_pmf(&table) {
+            assert!((p - 0.25).abs() < 1e-12);
+        }
+        assert!(AliasTable::new(&[]).is_empty());
+    }
+
+    #[test]
+    fn sample_frequencies() {
+        let weights = [3., 0., 1., 6.];
+        let table = AliasTable::new(&weights);
+        let mut count = [0usize; 4];
+        let n = 200_000;
+        for _ in 0..n {
+            let (i, pmf) = table.sample();
+            assert_eq!(pmf, table.pmf(i));
+            count[i] += 1;
+        }
+        assert_eq!(count[1], 0);
+        for i in 0..4 {
+            assert!((count[i] as f64 / n as f64 - weights[i] / 10.).abs() < 0.01);
+        }
+    }
+}
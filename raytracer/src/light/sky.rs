@@ -0,0 +1,214 @@
+use std::{f64::consts::PI, f64::INFINITY};
+
+use super::Background;
+use crate::{
+    basic::degree_to_radians,
+    bvh::aabb::AABB,
+    material::{Material, ScatterRecord, ONB},
+    pdf::random_to_sphere,
+    Hit::{Color, HitRecord, Hittable, Point3, Ray, Vec3},
+};
+
+// Preetham 解析天空模型(A Practical Analytic Model for Daylight, 1999)
+// reference: https://www.cs.utah.edu/~shirley/papers/sunsky/sunsky.pdf
+// 作为背景给出天空与太阳圆盘的亮度; 加入 lights 后太阳(有限大小的圆盘)作为光源被采样
+pub struct Sky {
+    sun_direction: Vec3,
+    turbidity: f64,
+    // Perez 分布函数的系数, 分别对应 Y, x, y
+    perez: [[f64; 5]; 3],
+    // 天顶的 Y, x, y, 以及 Y 的归一化系数
+    zenith: [f64; 3],
+    intensity: f64,
+    cos_sun_max: f64, // 太阳圆盘的角半径的余弦
+    sun_intensity: f64,
+    sun_color: Color,
+}
+
+impl Sky {
+    // elevation: 太阳高度角, azimuth: 方位角(从 +z 转向 +x), 均为角度; turbidity: 浑浊度, 一般取 2 ~ 10
+    pub fn new(elevation: f64, azimuth: f64, turbidity: f64) -> Self {
+        let elevation = degree_to_radians(elevation);
+        let azimuth = degree_to_radians(azimuth);
+        let sun_direction = Vec3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            elevation.cos() * azimuth.cos(),
+        );
+
+        let t = turbidity;
+        let perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        // 天顶处的亮度(kcd/m^2)与色度, 太阳在地平线以下时按地平线计算
+        let theta_s = (PI / 2. - elevation).min(PI / 2.);
+        let chi = (4. / 9. - t / 120.) * (PI - 2. * theta_s);
+        let zenith_luminance = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192).max(0.);
+        let cubic = |c: [f64; 4]| ((c[0] * theta_s + c[1]) * theta_s + c[2]) * theta_s + c[3];
+        let zenith_x = t * t * cubic([0.00166, -0.00375, 0.00209, 0.])
+            + t * cubic([-0.02903, 0.06377, -0.03202, 0.00394])
+            + cubic([0.11693, -0.21196, 0.06052, 0.25886]);
+        let zenith_y = t * t * cubic([0.00275, -0.00610, 0.00317, 0.])
+            + t * cubic([-0.04214, 0.08970, -0.04153, 0.00516])
+            + cubic([0.15346, -0.26756, 0.06670, 0.26688]);
+
+        let mut sky = Self {
+            sun_direction,
+            turbidity,
+            perez,
+            zenith: [zenith_luminance, zenith_x, zenith_y],
+            intensity: 1.,
+            cos_sun_max: 1.,
+            sun_intensity: 0.,
+            sun_color: Color::new(0., 0., 0.),
+        };
+        // 除以 F(0, theta_s), 使天顶处恰为天顶亮度
+        for i in 0..3 {
+            sky.zenith[i] /= sky.perez_function(i, 1., theta_s.cos());
+        }
+        sky.with_sun(0.53, 100.)
+    }
+
+    // 天空亮度的缩放, 默认使天顶亮度(kcd/m^2)直接作为辐射亮度
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    // angular_diameter: 太阳的视直径(角度); intensity: 大气衰减前太阳的辐照度(klux, 与天空亮度的单位一致)
+    pub fn with_sun(mut self, angular_diameter: f64, intensity: f64) -> Self {
+        self.cos_sun_max = degree_to_radians(angular_diameter / 2.).cos();
+        self.sun_intensity = intensity;
+        self.sun_color = self.sun_transmittance();
+        self
+    }
+
+    pub fn sun_direction(&self) -> Vec3 {
+        self.sun_direction
+    }
+
+    // F(theta, gamma) = (1 + A e^(B / cos(theta))) (1 + C e^(D gamma) + E cos^2(gamma))
+    fn perez_function(&self, i: usize, cos_theta: f64, cos_gamma: f64) -> f64 {
+        let [a, b, c, d, e] = self.perez[i];
+        let gamma = cos_gamma.clamp(-1., 1.).acos();
+        (1. + a * (b / cos_theta).exp()) * (1. + c * (d * gamma).exp() + e * cos_gamma * cos_gamma)
+    }
+
+    // 太阳光穿过大气后的透射率(只考虑 Rayleigh 与气溶胶散射), 分别取 R, G, B 的代表波长
+    fn sun_transmittance(&self) -> Color {
+        let theta_s = self.sun_direction.y().clamp(-1., 1.).acos();
+        if theta_s >= PI / 2. {
+            return Color::new(0., 0., 0.);
+        }
+        // 相对光学质量
+        let m = 1. / (theta_s.cos() + 0.15 * (93.885 - theta_s.to_degrees()).powf(-1.253));
+        let beta = 0.04608 * self.turbidity - 0.04586;
+        let tau = |lambda: f64| {
+            // lambda: 微米
+            let rayleigh = (-0.008735 * lambda.powf(-4.08) * m).exp();
+            let aerosol = (-beta * lambda.powf(-1.3) * m).exp();
+            rayleigh * aerosol
+        };
+        Color::new(tau(0.680), tau(0.550), tau(0.440))
+    }
+
+    fn sun_solid_angle(&self) -> f64 {
+        2. * PI * (1. - self.cos_sun_max)
+    }
+
+    fn sky_value(&self, dir: &Vec3) -> Color {
+        // 地平线以下沿用地平线的亮度
+        let cos_theta = dir.y().max(0.01);
+        let cos_gamma = Vec3::dot(dir, &self.sun_direction);
+        let luminance = self.zenith[0] * self.perez_function(0, cos_theta, cos_gamma);
+        let x = self.zenith[1] * self.perez_function(1, cos_theta, cos_gamma);
+        let y = self.zenith[2] * self.perez_function(2, cos_theta, cos_gamma);
+
+        // xyY -> XYZ -> 线性 sRGB
+        let cx = x / y * luminance;
+        let cz = (1. - x - y) / y * luminance;
+        let cy = luminance;
+        Color::new(
+            (3.2406 * cx - 1.5372 * cy - 0.4986 * cz).max(0.),
+            (-0.9689 * cx + 1.8758 * cy + 0.0415 * cz).max(0.),
+            (0.0557 * cx - 0.2040 * cy + 1.0570 * cz).max(0.),
+        ) * self.intensity
+    }
+
+    fn in_sun(&self, dir: &Vec3) -> bool {
+        Vec3::dot(dir, &self.sun_direction) >= self.cos_sun_max
+    }
+}
+
+impl Background for Sky {
+    fn value(&self, direction: &Vec3) -> Color {
+        let dir = direction.unit_vector();
+        let sky = self.sky_value(&dir);
+        if self.in_sun(&dir) {
+            sky + self.sun_color * (self.sun_intensity / self.sun_solid_angle())
+        } else {
+            sky
+        }
+    }
+}
+
+// 作为光源时, HitRecord 的 p 存放方向
+impl Material for Sky {
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord) -> Option<ScatterRecord> {
+        None
+    }
+    fn emitted(&self, _u: f64, _v: f64, p: &Point3) -> Option<Color> {
+        Some(self.value(p))
+    }
+}
+
+// 只有太阳圆盘作为光源, 位于无穷远处
+impl Hittable for Sky {
+    fn hit(&self, r: &Ray, _t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let dir = r.direction().unit_vector();
+        if t_max < INFINITY || !self.in_sun(&dir) {
+            return None;
+        }
+        Some(HitRecord::new(f64::MAX, dir, -dir, true, self, 0., 0.))
+    }
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        None
+    }
+
+    fn pdf_value(&self, _o: &Point3, v: &Vec3) -> f64 {
+        if self.in_sun(&v.unit_vector()) {
+            1. / self.sun_solid_angle()
+        } else {
+            0.
+        }
+    }
+    fn random(&self, _o: &Vec3) -> Vec3 {
+        let uvw = ONB::build(&self.sun_direction);
+        let sin_sun_max = (1. - self.cos_sun_max.powi(2)).sqrt();
+        uvw.local(random_to_sphere(sin_sun_max, 1.))
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+}
@@ -2,16 +2,24 @@
 pub mod Hit;
 pub mod basic;
 pub mod bvh;
+pub mod light;
 pub mod material;
 pub mod object;
 pub mod pdf;
 pub mod scene;
+pub mod sdf;
+pub mod spectrum;
 pub mod texture;
 
 use console::style;
 use image::{GenericImageView, ImageBuffer, Pixel, RgbImage};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use pdf::{hittablepdf::HittablePDF, mixturepdf::MixturePDF, PDF};
+use light::{Background, LightChoice, Lights};
+use material::{
+    dielectric::{Crossing, IorStack},
+    Bounce, ScatterRecord,
+};
+use spectrum::{rgb::RgbToSpectrum, ColorMode, SampledWavelengths};
 use std::{
     f64::INFINITY,
     fs::File,
@@ -26,54 +34,281 @@ use basic::{
     RAY::Ray,
     VEC3::{Color, Point3, Vec3},
 };
-use Hit::{Hittable, HittableList};
+use bvh::{
+    animated::AnimatedBvh,
+    stats::{take_test_counts, BvhStats},
+};
+use Hit::{HitRecord, Hittable, HittableList};
+
+// 光源采样(next event estimation): 按功率选择一个光源, 向其发出阴影射线
+// 面光源与按材质采样的结果用 balance heuristic 做MIS, 理想光源没有面积, 不需要MIS
+fn sample_light(
+    r: &Ray,
+    rec: &HitRecord,
+    srec: &ScatterRecord,
+    world: &HittableList,
+    lights: &Lights,
+    mode: ColorMode,
+) -> Color {
+    let (light, pmf) = match lights.choose() {
+        Some(choice) => choice,
+        None => return Color::new(0., 0., 0.),
+    };
+
+    let (shadow_ray, emitted, transmittance, weight) = match light {
+        LightChoice::Area(light) => {
+            let shadow_ray = Ray::new(rec.p, light.random(&rec.p), r.time());
+            let light_val = lights.pdf_value(&rec.p, &shadow_ray.direction());
+            if light_val <= 0. {
+                return Color::new(0., 0., 0.);
+            }
+
+            let light_rec = match lights.area().hit(&shadow_ray, 0.001, INFINITY) {
+                Some(light_rec) => light_rec,
+                None => return Color::new(0., 0., 0.),
+            };
+            let transmittance = world.transmittance(&shadow_ray, 0.001, light_rec.t - 0.001);
+
+            let emitted = mode
+                .emitted(light_rec.mat, &shadow_ray, &light_rec)
+                .unwrap();
+            let scatter_val = srec
+                .pdf_ptr
+                .as_ref()
+                .unwrap()
+                .value(&shadow_ray.direction());
+            let weight = light_val / (light_val + scatter_val);
+
+            (shadow_ray, emitted, transmittance, weight / light_val)
+        }
+        LightChoice::Delta(light) => {
+            let sample = match light.sample(&rec.p) {
+                Some(sample) => sample,
+                None => return Color::new(0., 0., 0.),
+            };
+            let shadow_ray = Ray::new(rec.p, sample.direction, r.time());
+            let transmittance = world.transmittance(&shadow_ray, 0.001, sample.distance - 0.001);
+
+            let irradiance = mode.illuminant(sample.irradiance);
+            (shadow_ray, irradiance, transmittance, 1. / pmf)
+        }
+    };
+    if transmittance <= 0. {
+        return Color::new(0., 0., 0.);
+    }
+
+    let tint = rec.mat.scatter_tint(r, rec, &shadow_ray);
+    emitted
+        * srec.attenuation
+        * mode.albedo(tint)
+        * (rec.mat).scatter_pdf(r, rec, &shadow_ray).unwrap()
+        * weight
+        * transmittance
+}
 
-fn ray_color(
+// bounce: 光线来自哪种顶点, 决定击中光源时是否可见及其MIS权重
+// stack: 光线当前所在的(嵌套)电介质
+// mode: RGB 或光谱模式, 光谱模式下返回各采样波长处的辐射亮度
+#[allow(clippy::too_many_arguments)]
+fn ray_color<'a>(
     r: Ray,
-    background: Color,
-    world: &HittableList,
-    lights: &HittableList,
+    background: &dyn Background,
+    world: &'a HittableList,
+    lights: &Lights,
     depth: i32,
+    bounce: Bounce,
+    stack: &IorStack<'a>,
+    mode: ColorMode,
 ) -> Color {
     if depth <= 0 {
         // 反射过多次, 可认为碰到了一个corner, 直接返回(0,0,0)无光
         return Color::new(0., 0., 0.);
     }
 
-    if let Some(rec) = world.hit(&r, 0.001, INFINITY) {
-        let emitted = rec.mat.emitted(rec.u, rec.v, &rec.p).unwrap(); // 击中物体本身发光程度(目前只有diffuse材质会emit light)
-        if let Some(ScatterRecord) = (rec.mat).scatter(&r, &rec) {
-            if ScatterRecord.is_specular {
-                return ScatterRecord.attenuation
+    let rec = match world.hit(&r, 0.001, INFINITY) {
+        Some(rec) => rec,
+        // 环境光源已参与光源采样时, 同样需要MIS权重
+        None => {
+            return mode.illuminant(background.value(&r.direction())) * bounce.emission_weight()
+        }
+    };
+
+    // 在电介质内部时, 这段光路穿过其内部介质: 可能中途散射, 并按经过的距离吸收
+    let mut absorbed = Color::new(1., 1., 1.);
+    if let Some(interior) = stack.interior() {
+        let ray_length = r.direction().len();
+        let distance = rec.t * ray_length;
+        let scatter_distance = if interior.scattering > 0. {
+            -(1. - random_double()).ln() / interior.scattering
+        } else {
+            INFINITY
+        };
+        let travelled = distance.min(scatter_distance);
+        let absorption = mode.unbounded(interior.absorption);
+        absorbed = Color::new(
+            (-absorption.x * travelled).exp(),
+            (-absorption.y * travelled).exp(),
+            (-absorption.z * travelled).exp(),
+        );
+
+        if scatter_distance < distance {
+            let t = scatter_distance / ray_length;
+            let medium_rec = HitRecord::new(
+                t,
+                r.at(t),
+                Vec3::new(1., 0., 0.),
+                true,
+                &interior.phase,
+                0.,
+                0.,
+            );
+            return absorbed
+                * shade(
+                    &r,
+                    &medium_rec,
+                    background,
+                    world,
+                    lights,
+                    depth,
+                    bounce,
+                    stack,
+                    mode,
+                );
+        }
+    }
+
+    // 对这类光线隐藏的物体(如灯具), 光线直接穿过
+    if !rec.mat.visible_to(bounce) {
+        return absorbed
+            * ray_color(
+                Ray::new(rec.p, r.direction(), r.time()),
+                background,
+                world,
+                lights,
+                depth - 1,
+                bounce,
+                stack,
+                mode,
+            );
+    }
+
+    // 电介质的两侧折射率由栈决定, 被高优先级物体包含的表面直接穿过
+    // 穿过的不是真正的界面, 与介质内的继续传播一样不消耗深度
+    if let Some(dielectric) = rec.mat.as_dielectric() {
+        let color = match stack.cross(dielectric, rec.front_face, mode.wavelength()) {
+            Crossing::Skip(next) => ray_color(
+                Ray::new(rec.p, r.direction(), r.time()),
+                background,
+                world,
+                lights,
+                depth,
+                bounce,
+                &next,
+                mode,
+            ),
+            Crossing::Interface {
+                n1,
+                n2,
+                refracted,
+                dispersive,
+            } => {
+                let (scattered, is_refracted, weight) =
+                    dielectric.scatter_between(&r, &rec, n1, n2, &mode);
+                let next = if is_refracted {
+                    refracted
+                } else {
+                    stack.clone()
+                };
+                // 折射率随波长变化时各波长的光路分开, 此后只追踪 hero 波长
+                let dispersed = if dispersive {
+                    mode.terminate_secondary()
+                } else {
+                    None
+                };
+                let color = weight
                     * ray_color(
-                        ScatterRecord.specular_ray,
+                        scattered,
                         background,
                         world,
                         lights,
                         depth - 1,
+                        Bounce::Specular,
+                        &next,
+                        dispersed.unwrap_or(mode),
                     );
+                match dispersed {
+                    Some(_) => SampledWavelengths::hero_only(color),
+                    None => color,
+                }
             }
+        };
+        return absorbed * color;
+    }
 
-            // 这部分目前就是Lambertian材质的Tracer
-            let light_ptr = HittablePDF::new(lights, rec.p); // 按光源位置分布的pdf
-
-            let mix_pdf = MixturePDF::new(light_ptr, ScatterRecord.pdf_ptr.unwrap()); // 将light_pdf 与 cos分布(如lambertian)进行mixture
-
-            let scattered = Ray::new(rec.p, mix_pdf.generate(), r.time());
-            let pdf_val = mix_pdf.value(&scattered.direction());
-            // println!("$ {}", light_ptr.value(&scattered.direction()));
+    absorbed
+        * shade(
+            &r, &rec, background, world, lights, depth, bounce, stack, mode,
+        )
+}
 
-            //println!("{:?} {}", ScatterRecord.attenuation, pdf_val);
-            emitted
+// 在交点处按材质散射, 漫反射类材质同时做光源采样
+#[allow(clippy::too_many_arguments)]
+fn shade<'a>(
+    r: &Ray,
+    rec: &HitRecord,
+    background: &dyn Background,
+    world: &'a HittableList,
+    lights: &Lights,
+    depth: i32,
+    bounce: Bounce,
+    stack: &IorStack<'a>,
+    mode: ColorMode,
+) -> Color {
+    let emitted = mode.emitted(rec.mat, r, rec).unwrap() * bounce.emission_weight(); // 击中物体本身发光程度(目前只有diffuse材质会emit light)
+    if let Some(ScatterRecord) = mode.scatter(rec.mat, r, rec) {
+        if ScatterRecord.is_specular {
+            return emitted
                 + ScatterRecord.attenuation
-                    * (rec.mat).scatter_pdf(&r, &rec, &scattered).unwrap()
-                    * ray_color(scattered, background, world, lights, depth - 1)
-                    / pdf_val
-        } else {
-            emitted
+                    * ray_color(
+                        ScatterRecord.specular_ray,
+                        background,
+                        world,
+                        lights,
+                        depth - 1,
+                        Bounce::Specular,
+                        stack,
+                        mode,
+                    );
         }
+
+        // 这部分目前就是Lambertian材质的Tracer
+        let pdf_ptr = ScatterRecord.pdf_ptr.as_ref().unwrap();
+        let scattered = Ray::new(rec.p, pdf_ptr.generate(), r.time());
+        let pdf_val = pdf_ptr.value(&scattered.direction());
+
+        let light_val = lights.pdf_value(&rec.p, &scattered.direction());
+        let direct = sample_light(r, rec, &ScatterRecord, world, lights, mode);
+        let next_weight = pdf_val / (pdf_val + light_val);
+        let tint = rec.mat.scatter_tint(r, rec, &scattered);
+
+        emitted
+            + direct
+            + ScatterRecord.attenuation
+                * mode.albedo(tint)
+                * (rec.mat).scatter_pdf(r, rec, &scattered).unwrap()
+                * ray_color(
+                    scattered,
+                    background,
+                    world,
+                    lights,
+                    depth - 1,
+                    Bounce::Diffuse(next_weight),
+                    stack,
+                    mode,
+                )
+                / pdf_val
     } else {
-        background
+        emitted
     }
 }
 // 在每个像素点周围(小范围)内采样sample_per_pixel次, 暴力取平均值
@@ -171,108 +406,44 @@ pub fn edge_detect() {
     }
     std::process::exit(0);
 }
-fn main() {
-    edge_detect();
-
-    const THREAD_NUMBER: usize = 8;
-
-    // Image
-    const RATIO: f64 = 1.;
-    const IMAGE_WIDTH: usize = 600;
-    const IMAGE_HEIGHT: usize = (IMAGE_WIDTH as f64 / RATIO) as usize;
-    const SAMPLES_PER_PIXEL: usize = 10;
-    const MAX_DEPTH: i32 = 50;
-
-    let quality = 100;
-    let path = "output/output.jpg";
-
-    // World
-
-    let aperture = 0.;
-    let background = Color::new(0., 0., 0.);
-    let lf = Point3::new(278., 278., -800.);
-    let la = Point3::new(278., 278., 0.);
-    let vfov = 30.;
-
-    let (world, lights) = scene::cornell_box();
-
-    /*
-    let switch = 6;
-    match switch {
-        0 => {
-            world = HittableList::two_sphere();
-        }
-        1 => {
-            world = HittableList::two_perlin_sphere();
-        }
-        2 => {
-            world = HittableList::load_image();
-        }
-        3 => {
-            world = HittableList::simple_light();
-            background = Color::new(0., 0., 0.);
-            lf = Point3::new(26., 3., 6.);
-            la = Point3::new(0., 2., 0.);
-        }
-        4 => {
-            world = HittableList::cornell_box_smoke();
-            background = Color::new(0., 0., 0.);
-            lf = Point3::new(278., 278., -800.);
-            la = Point3::new(278., 278., 0.);
-            vfov = 40.;
-        }
-        5 => {
-            world = HittableList::final_scene();
-            background = Color::new(0., 0., 0.);
-            lf = Point3::new(478., 278., -600.);
-            la = Point3::new(278., 278., 0.);
-            vfov = 40.;
-        }
-        6 => {
-            world = HittableList::cornell_box();
-            background = Color::new(0., 0., 0.);
-            lf = Point3::new(278., 278., -800.);
-            la = Point3::new(278., 278., 0.);
-            vfov = 40.;
-        }
-        _ => {
-            world = HittableList::random_scene();
-            aperture = 0.1;
-        }
-    }
-    */
-
-    // Camera
-
-    let cam = Camera::new(
-        lf,
-        la,
-        Vec3::new(0., 1., 0.),
-        vfov,
-        RATIO,
-        aperture,
-        10.,
-        0.,
-        1.,
-    );
-
-    // Render
-    println!(
-        "         Image size:                {}",
-        style(IMAGE_WIDTH.to_string() + &"x".to_string() + &IMAGE_HEIGHT.to_string()).yellow()
-    );
-    println!(
-        "         Sample number per pixel:   {}",
-        style(SAMPLES_PER_PIXEL.to_string()).yellow()
-    );
-    println!(
-        "         Reflection max depth:      {}",
-        style(MAX_DEPTH.to_string()).yellow()
-    );
-
+const THREAD_NUMBER: usize = 8;
+
+// Image
+const RATIO: f64 = 1.;
+const IMAGE_WIDTH: usize = 600;
+const IMAGE_HEIGHT: usize = (IMAGE_WIDTH as f64 / RATIO) as usize;
+const SAMPLES_PER_PIXEL: usize = 10;
+const MAX_DEPTH: i32 = 50;
+
+// 场景: 0 为 Cornell box, 1 为运动模糊(关键帧动画的立方体与obj模型, 需要 SHUTTER_TIME > 0)
+const SCENE: usize = 0;
+
+// Animation: 第i帧的快门区间为 [i * FRAME_TIME, i * FRAME_TIME + SHUTTER_TIME]
+const FRAME_NUMBER: usize = 1;
+const FRAME_TIME: f64 = 1.;
+const SHUTTER_TIME: f64 = 1.;
+const BVH_REBUILD_RATE: f64 = 0.5; // SAH代价恶化50%后重建BVH
+
+// 不做正常渲染, 而是输出相机光线的 AABB::hit 次数与物体求交次数热力图
+// 计数有额外开销, 需要 cargo run --release --features heatmap
+const HEATMAP: bool = cfg!(feature = "heatmap");
+
+// 光谱渲染: 每条路径采样若干波长, 可以表现色散与测量得到的光源光谱
+const SPECTRAL: bool = false;
+// RGB 到光谱的转换表拟合较慢, 第一次拟合后缓存在这里, 之后直接读取
+const SPECTRUM_TABLE: &str = "output/rgb_to_spectrum.coeff";
+
+// 多线程渲染一帧, 返回从下到上逐行的像素颜色(未除以采样数)
+// spectrum: 光谱模式下 RGB 到光谱的转换表, None 为 RGB 模式
+fn render(
+    cam: Camera,
+    background: Arc<dyn Background>,
+    world: HittableList,
+    lights: Lights,
+    spectrum: Option<Arc<RgbToSpectrum>>,
+) -> Vec<Color> {
     const SECTION_LINE_NUM: usize = IMAGE_HEIGHT / THREAD_NUMBER;
 
-    let mut img: RgbImage = ImageBuffer::new(IMAGE_WIDTH as u32, IMAGE_HEIGHT as u32);
     let mut output_pixel_color = Vec::<Color>::new(); // store pixels
     let mut thread_pool = Vec::<_>::new(); // store closures
 
@@ -298,6 +469,8 @@ fn main() {
 
         let clone_world = world.clone(); // due to multithread's ownership problem
         let clone_lights = lights.clone();
+        let clone_background = background.clone();
+        let clone_spectrum = spectrum.clone();
 
         thread_pool.push((
             thread::spawn(move || {
@@ -315,8 +488,17 @@ fn main() {
                             let u = (i as f64 + random_double()) / (IMAGE_WIDTH as f64 - 1.);
                             let v = (j as f64 + random_double()) / (IMAGE_HEIGHT as f64 - 1.);
                             let r = cam.get_ray(u, v);
-                            pixel_color +=
-                                ray_color(r, background, &clone_world, &clone_lights, MAX_DEPTH);
+                            let mode = ColorMode::new(clone_spectrum.as_deref(), random_double());
+                            pixel_color += mode.to_rgb(ray_color(
+                                r,
+                                &*clone_background,
+                                &clone_world,
+                                &clone_lights,
+                                MAX_DEPTH,
+                                Bounce::Camera,
+                                &IorStack::default(),
+                                mode,
+                            ));
                         }
                         section_pixel_color.push(pixel_color);
                         progress += 1;
@@ -360,6 +542,12 @@ fn main() {
     }
     collecting_progress_bar.finish_and_clear();
 
+    output_pixel_color
+}
+
+fn output_image(output_pixel_color: &[Color], path: &str, quality: u8) {
+    let mut img: RgbImage = ImageBuffer::new(IMAGE_WIDTH as u32, IMAGE_HEIGHT as u32);
+
     let mut pixel_id = 0;
     for j in 0..IMAGE_HEIGHT as u32 {
         for i in 0..IMAGE_WIDTH as u32 {
@@ -384,6 +572,251 @@ fn main() {
         // Err(_) => panic!("Outputting image fails."),
         Err(_) => println!("{}", style("Outputting image fails.").red()),
     }
+}
+
+// 求交次数映射为颜色: 0 -> 蓝, 0.5 -> 绿, 1 -> 红
+fn heatmap_color(x: f64) -> [u8; 3] {
+    let x = clamp(x, 0., 1.);
+    let color = if x < 0.25 {
+        lerp(Color::new(0., 0., 1.), Color::new(0., 1., 1.), x / 0.25)
+    } else if x < 0.5 {
+        lerp(
+            Color::new(0., 1., 1.),
+            Color::new(0., 1., 0.),
+            (x - 0.25) / 0.25,
+        )
+    } else if x < 0.75 {
+        lerp(
+            Color::new(0., 1., 0.),
+            Color::new(1., 1., 0.),
+            (x - 0.5) / 0.25,
+        )
+    } else {
+        lerp(
+            Color::new(1., 1., 0.),
+            Color::new(1., 0., 0.),
+            (x - 0.75) / 0.25,
+        )
+    };
+    [
+        (255.999 * color.x) as u8,
+        (255.999 * color.y) as u8,
+        (255.999 * color.z) as u8,
+    ]
+}
+
+fn output_heatmap(counts: &[u64], path: &str, quality: u8) {
+    let max_count = counts.iter().copied().max().unwrap_or(0).max(1);
+    let mut img: RgbImage = ImageBuffer::new(IMAGE_WIDTH as u32, IMAGE_HEIGHT as u32);
+
+    let mut pixel_id = 0;
+    for j in 0..IMAGE_HEIGHT as u32 {
+        for i in 0..IMAGE_WIDTH as u32 {
+            let pixel = img.get_pixel_mut(i, IMAGE_HEIGHT as u32 - j - 1);
+            *pixel = image::Rgb(heatmap_color(counts[pixel_id] as f64 / max_count as f64));
+            pixel_id += 1;
+        }
+    }
+    println!(
+        "Output heatmap as \"{}\" (max {} tests per ray)",
+        style(path).yellow(),
+        style(max_count.to_string()).yellow()
+    );
+    let output_image = image::DynamicImage::ImageRgb8(img);
+    let mut output_file = File::create(path).unwrap();
+    match output_image.write_to(&mut output_file, image::ImageOutputFormat::Jpeg(quality)) {
+        Ok(_) => {}
+        Err(_) => println!("{}", style("Outputting image fails.").red()),
+    }
+}
+
+// 每个像素只发一条穿过像素中心的相机光线, 统计其求交次数
+fn render_heatmap(cam: Camera, world: &HittableList, quality: u8) {
+    let mut aabb_counts = Vec::<u64>::new();
+    let mut primitive_counts = Vec::<u64>::new();
+
+    for j in 0..IMAGE_HEIGHT {
+        for i in 0..IMAGE_WIDTH {
+            let u = (i as f64 + 0.5) / (IMAGE_WIDTH as f64 - 1.);
+            let v = (j as f64 + 0.5) / (IMAGE_HEIGHT as f64 - 1.);
+            let r = cam.get_ray(u, v);
+
+            take_test_counts();
+            world.hit(&r, 0.001, INFINITY);
+            let (aabb_tests, primitive_tests) = take_test_counts();
+            aabb_counts.push(aabb_tests);
+            primitive_counts.push(primitive_tests);
+        }
+    }
+
+    output_heatmap(&aabb_counts, "output/heatmap_aabb.jpg", quality);
+    output_heatmap(&primitive_counts, "output/heatmap_primitive.jpg", quality);
+}
+
+// 读取缓存的转换表, 没有时拟合并写入缓存
+fn load_spectrum_table() -> RgbToSpectrum {
+    if let Some(table) = RgbToSpectrum::load(SPECTRUM_TABLE) {
+        return table;
+    }
+
+    println!("{}", style("Fitting RGB to spectrum table:").yellow());
+    let progress_bar = ProgressBar::new(RgbToSpectrum::columns() as u64);
+    progress_bar.set_style(ProgressStyle::default_bar()
+    .template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] [{pos}/{len}] ({eta})")
+    .progress_chars("#>-"));
+    let table = RgbToSpectrum::fit(|| progress_bar.inc(1));
+    progress_bar.finish_and_clear();
+
+    match table.save(SPECTRUM_TABLE) {
+        Ok(()) => println!(
+            "Cached spectrum table as \"{}\"",
+            style(SPECTRUM_TABLE).yellow()
+        ),
+        Err(e) => println!(
+            "{} \"{}\": {}",
+            style("Failed to cache spectrum table").red(),
+            SPECTRUM_TABLE,
+            e
+        ),
+    }
+    table
+}
+
+fn main() {
+    edge_detect();
+
+    let quality = 100;
+
+    // World
+
+    let aperture = 0.;
+    let background: Arc<dyn Background> = Arc::new(Color::new(0., 0., 0.));
+    let lf = Point3::new(278., 278., -800.);
+    let la = Point3::new(278., 278., 0.);
+    let vfov = 30.;
+
+    let (world, lights) = match SCENE {
+        1 => scene::motion_blur(),
+        _ => scene::cornell_box(),
+    };
+    let lights = Lights::new(lights);
+
+    /*
+    let switch = 6;
+    match switch {
+        0 => {
+            world = HittableList::two_sphere();
+        }
+        1 => {
+            world = HittableList::two_perlin_sphere();
+        }
+        2 => {
+            world = HittableList::load_image();
+        }
+        3 => {
+            world = HittableList::simple_light();
+            background = Color::new(0., 0., 0.);
+            lf = Point3::new(26., 3., 6.);
+            la = Point3::new(0., 2., 0.);
+        }
+        4 => {
+            world = HittableList::cornell_box_smoke();
+            background = Color::new(0., 0., 0.);
+            lf = Point3::new(278., 278., -800.);
+            la = Point3::new(278., 278., 0.);
+            vfov = 40.;
+        }
+        5 => {
+            world = HittableList::final_scene();
+            background = Color::new(0., 0., 0.);
+            lf = Point3::new(478., 278., -600.);
+            la = Point3::new(278., 278., 0.);
+            vfov = 40.;
+        }
+        6 => {
+            world = HittableList::cornell_box();
+            background = Color::new(0., 0., 0.);
+            lf = Point3::new(278., 278., -800.);
+            la = Point3::new(278., 278., 0.);
+            vfov = 40.;
+        }
+        _ => {
+            world = HittableList::random_scene();
+            aperture = 0.1;
+        }
+    }
+    */
+
+    // 转换表只需拟合一次, 所有帧共用
+    let spectrum = if SPECTRAL {
+        Some(Arc::new(load_spectrum_table()))
+    } else {
+        None
+    };
+
+    // 动画中每帧只refit, 必要时才重建
+    let mut world = AnimatedBvh::new(world, 0., SHUTTER_TIME, BVH_REBUILD_RATE);
+
+    // Render
+    println!(
+        "         Image size:                {}",
+        style(IMAGE_WIDTH.to_string() + &"x".to_string() + &IMAGE_HEIGHT.to_string()).yellow()
+    );
+    println!(
+        "         Sample number per pixel:   {}",
+        style(SAMPLES_PER_PIXEL.to_string()).yellow()
+    );
+    println!(
+        "         Reflection max depth:      {}",
+        style(MAX_DEPTH.to_string()).yellow()
+    );
+
+    for frame in 0..FRAME_NUMBER {
+        let time0 = frame as f64 * FRAME_TIME;
+        let time1 = time0 + SHUTTER_TIME;
+
+        if world.update(time0, time1) {
+            println!("Frame {}: {}", frame, style("BVH rebuilt").yellow());
+        }
+
+        // Camera
+
+        let cam = Camera::new(
+            lf,
+            la,
+            Vec3::new(0., 1., 0.),
+            vfov,
+            RATIO,
+            aperture,
+            10.,
+            time0,
+            time1,
+        );
+
+        let mut frame_world = HittableList::default();
+        frame_world.add(world.root());
+
+        if HEATMAP {
+            BvhStats::new(&world, time0, time1).report();
+            render_heatmap(cam, &frame_world, quality);
+            continue;
+        }
+
+        let output_pixel_color = render(
+            cam,
+            background.clone(),
+            frame_world,
+            lights.clone(),
+            spectrum.clone(),
+        );
+
+        let path = if FRAME_NUMBER == 1 {
+            String::from("output/output.jpg")
+        } else {
+            format!("output/frame_{}.jpg", frame)
+        };
+        output_image(&output_pixel_color, &path, quality);
+    }
 
     exit(0);
 }
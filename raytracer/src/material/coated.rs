@@ -0,0 +1,128 @@
+use super::{Color, HitRecord, Material, Ray, ScatterRecord, Vec3};
+use crate::{
+    basic::random_double,
+    spectrum::{ColorMode, Spectral},
+};
+
+// 在任意基底材质(Lambertian, Metal 等)上覆盖一层透明涂层: 车漆, 清漆木材, 塑料
+// 一次散射只能返回一条 ScatterRecord, 因此按涂层的 Fresnel 反射率随机选择:
+//   概率 F 在涂层表面镜面反射, 否则穿过涂层由基底散射, 进出涂层时按厚度吸收
+// 涂层与基底之间的多次反射忽略不计
+#[derive(Clone)]
+pub struct Coated<M: Material> {
+    pub base: M,
+    pub ior: f64,
+    pub tint: Color,    // 垂直穿过单位厚度的涂层后的透射率, (1, 1, 1) 为无色
+    pub thickness: f64, // 涂层厚度, 与 tint 一起决定吸收
+    pub roughness: f64, // 涂层表面的粗糙度, 与 Metal 的 fuzz 相同
+}
+
+impl<M: Material> Coated<M> {
+    pub fn new(base: M, ior: f64) -> Self {
+        Self {
+            base,
+            ior,
+            tint: Color::new(1., 1., 1.),
+            thickness: 0.,
+            roughness: 0.,
+        }
+    }
+    pub fn with_tint(mut self, tint: Color, thickness: f64) -> Self {
+        self.tint = tint;
+        self.thickness = thickness;
+        self
+    }
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.roughness = roughness.clamp(0., 1.);
+        self
+    }
+
+    fn fresnel(&self, cosine: f64) -> f64 {
+        let r = ((1. - self.ior) / (1. + self.ior)).powi(2);
+        r + (1. - r) * (1. - cosine).powi(5)
+    }
+
+    // 以 cosine 从外侧进入(或离开)涂层时, 在涂层内走过的路程对应的透射率
+    fn transmittance(&self, cosine: f64) -> Color {
+        if self.thickness <= 0. {
+            return Color::new(1., 1., 1.);
+        }
+        let sin2 = (1. - cosine * cosine) / (self.ior * self.ior);
+        let distance = self.thickness / (1. - sin2).max(1e-6).sqrt();
+        Color::new(
+            self.tint.x.max(0.).powf(distance),
+            self.tint.y.max(0.).powf(distance),
+            self.tint.z.max(0.).powf(distance),
+        )
+    }
+
+    // 沿 direction 穿出涂层时透过界面的比例
+    fn exit_fresnel(&self, rec: &HitRecord, direction: &Vec3) -> f64 {
+        let cosine = Vec3::dot(&direction.unit_vector(), &rec.normal);
+        if cosine <= 0. {
+            return 0.;
+        }
+        1. - self.fresnel(cosine)
+    }
+    fn exit_transmittance(&self, rec: &HitRecord, direction: &Vec3) -> Color {
+        let cosine = Vec3::dot(&direction.unit_vector(), &rec.normal);
+        self.transmittance(cosine.clamp(0., 1.))
+    }
+
+    // attenuation 为 mode 下的颜色, 基底在光谱模式下也按各采样波长散射
+    fn layer(&self, r_in: &Ray, rec: &HitRecord, mode: &ColorMode) -> Option<ScatterRecord> {
+        let unit_direction = r_in.direction().unit_vector();
+        let cos_theta = Vec3::dot(&-unit_direction, &rec.normal).clamp(0., 1.);
+
+        if random_double() < self.fresnel(cos_theta) {
+            // 涂层表面的反射, 被选中的概率与反射率抵消
+            let reflected = Vec3::reflect(&unit_direction, &rec.normal);
+            let mut direction = reflected + self.roughness * Vec3::random_in_unit_sphere();
+            if Vec3::dot(&direction, &rec.normal) <= 0. {
+                direction = reflected;
+            }
+            return Some(ScatterRecord {
+                attenuation: Color::new(1., 1., 1.),
+                is_specular: true,
+                specular_ray: Ray::new(rec.p, direction, r_in.time()),
+                pdf_ptr: None,
+            });
+        }
+
+        // 透过涂层的概率 1 - F 与透射率抵消, 只剩进入时的吸收
+        let mut srec = mode.scatter(&self.base, r_in, rec)?;
+        srec.attenuation = srec.attenuation * mode.albedo(self.transmittance(cos_theta));
+        if srec.is_specular {
+            // 镜面基底的出射方向已知, 直接计入穿出涂层的部分
+            let direction = srec.specular_ray.direction();
+            srec.attenuation = srec.attenuation
+                * mode.albedo(self.exit_transmittance(rec, &direction))
+                * self.exit_fresnel(rec, &direction);
+        }
+        Some(srec)
+    }
+}
+
+impl<M: Material> Material for Coated<M> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        self.layer(r_in, rec, &ColorMode::Rgb)
+    }
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        spectral: &Spectral,
+    ) -> Option<ScatterRecord> {
+        self.layer(r_in, rec, &ColorMode::Spectral(*spectral))
+    }
+
+    // 非镜面基底: 穿出涂层的部分随出射方向变化, 光源采样与按材质采样共用
+    fn scatter_pdf(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Option<f64> {
+        let value = self.base.scatter_pdf(r_in, rec, scattered)?;
+        Some(value * self.exit_fresnel(rec, &scattered.direction()))
+    }
+    fn scatter_tint(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        self.base.scatter_tint(r_in, rec, scattered)
+            * self.exit_transmittance(rec, &scattered.direction())
+    }
+}
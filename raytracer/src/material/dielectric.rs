@@ -1,54 +1,387 @@
-use super::{Color, HitRecord, Material, Ray, ScatterRecord, Vec3};
-use crate::basic::random_double;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use super::thin_film::{FilmTable, ThinFilm};
+use super::{volume::Volume, Color, HitRecord, Material, Ray, ScatterRecord, Vec3};
+use crate::{
+    basic::random_double, pdf::phase::HenyeyGreenstein, spectrum::ColorMode,
+    texture::solid_color::SolidColor,
+};
+
+// 每个电介质物体的编号, clone出的材质(如Cube的六个面)视为同一个物体
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// 电介质内部的介质: 按 Beer-Lambert 吸收, 并可以按 HG 相函数散射
+#[derive(Clone)]
+pub struct Interior {
+    pub absorption: Color, // 每单位距离的吸收系数
+    pub scattering: f64,   // 每单位距离的散射系数
+    pub phase: Volume<SolidColor, HenyeyGreenstein>,
+}
+
+impl Interior {
+    pub fn new(absorption: Color, scattering: f64, albedo: Color, g: f64) -> Self {
+        Self {
+            absorption,
+            scattering,
+            phase: Volume::with_phase(
+                SolidColor::new(albedo.x, albedo.y, albedo.z),
+                HenyeyGreenstein::new(g),
+            ),
+        }
+    }
+}
+
+// 折射率随波长的变化(色散), 公式中波长的单位为 μm
+#[derive(Clone, Copy)]
+pub enum Dispersion {
+    // 阿贝数, 越小色散越强; 与 n_d 一起确定 Cauchy 公式
+    Abbe(f64),
+    // n = A + B / λ^2
+    Cauchy { a: f64, b: f64 },
+    // n^2 = 1 + Σ B_i λ^2 / (λ^2 - C_i)
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+// 以下波长单位为 μm
+const LAMBDA_D: f64 = 0.5876;
+const LAMBDA_F: f64 = 0.4861;
+const LAMBDA_C: f64 = 0.6563;
+
+#[derive(Clone)]
 pub struct Dielectric {
-    pub ir: f64, // Index of Refraction
+    pub ir: f64, // Index of Refraction, 有色散时为 d 线(587.6nm)处的折射率, RGB 模式下使用
+    pub dispersion: Option<Dispersion>, // 只在光谱模式下生效
+    film: Option<(ThinFilm, [FilmTable; 2])>, // 表面的薄膜, 反射率随波长与角度变化; 附带从外/内侧入射时 RGB 模式的查找表
+    // 嵌套时重叠部分属于优先级高的物体(如玻璃杯壁与杯中的水), 相同时后进入者优先
+    pub priority: i32,
+    pub interior: Option<Interior>,
+    id: usize,
 }
 
 impl Dielectric {
     pub fn new(index: f64) -> Self {
-        Self { ir: index }
+        Self {
+            ir: index,
+            dispersion: None,
+            film: None,
+            priority: 0,
+            interior: None,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+    // 例: BK7 玻璃 A = 1.5046, B = 0.0042
+    pub fn new_cauchy(a: f64, b: f64) -> Self {
+        Self::new_dispersion(Dispersion::Cauchy { a, b })
+    }
+    // 例: BK7 玻璃 B = [1.03961212, 0.231792344, 1.01046945], C = [0.00600069867, 0.0200179144, 103.560653]
+    pub fn new_sellmeier(b: [f64; 3], c: [f64; 3]) -> Self {
+        Self::new_dispersion(Dispersion::Sellmeier { b, c })
+    }
+    fn new_dispersion(dispersion: Dispersion) -> Self {
+        let mut dielectric = Self::new(1.);
+        dielectric.dispersion = Some(dispersion);
+        dielectric.ir = dielectric.ior(Some(LAMBDA_D * 1e3));
+        dielectric
+    }
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+    pub fn with_interior(mut self, interior: Interior) -> Self {
+        self.interior = Some(interior);
+        self
+    }
+    // 例: 冕牌玻璃 1.517 / 64, 火石玻璃 1.62 / 36, 钻石 2.417 / 55
+    pub fn with_abbe(mut self, abbe: f64) -> Self {
+        self.dispersion = Some(Dispersion::Abbe(abbe));
+        self
+    }
+    // 肥皂泡: Dielectric::new(1.) 加上一层水膜
+    pub fn with_thin_film(mut self, film: ThinFilm) -> Self {
+        let tables = [film.rgb_table(1., self.ir), film.rgb_table(self.ir, 1.)];
+        self.film = Some((film, tables));
+        self
+    }
+
+    pub fn dispersive(&self) -> bool {
+        self.dispersion.is_some()
+    }
+    // 波长 lambda(nm) 处的折射率
+    pub fn ior(&self, lambda: Option<f64>) -> f64 {
+        let (dispersion, lambda) = match (self.dispersion, lambda) {
+            (Some(dispersion), Some(lambda)) => (dispersion, lambda * 1e-3),
+            _ => return self.ir,
+        };
+        let l2 = lambda * lambda;
+        match dispersion {
+            Dispersion::Abbe(abbe) => {
+                let b = (self.ir - 1.)
+                    / abbe
+                    / (1. / (LAMBDA_F * LAMBDA_F) - 1. / (LAMBDA_C * LAMBDA_C));
+                self.ir + b * (1. / l2 - 1. / (LAMBDA_D * LAMBDA_D))
+            }
+            Dispersion::Cauchy { a, b } => a + b / l2,
+            Dispersion::Sellmeier { b, c } => {
+                let mut n2 = 1.;
+                for i in 0..3 {
+                    n2 += b[i] * l2 / (l2 - c[i]);
+                }
+                n2.max(1.).sqrt()
+            }
+        }
+    }
+    pub fn id(&self) -> usize {
+        self.id
     }
 
     fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
         let r = ((1. - ref_idx) / (1. + ref_idx)).powi(2);
         r + (1. - r) * (1. - cosine).powi(5)
     }
-}
-
-impl Material for Dielectric {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
-        let attenuation = Color::new(1., 1., 1.); // those kind of material absorbs nothing !
-
-        let refraction_ratio = if rec.front_face {
-            1. / self.ir
-        } else {
-            self.ir
-        }; // 折射率之比(要判断光线是在光密还是在光疏部分)
 
+    // n1: 入射侧折射率, n2: 透射侧折射率
+    // 返回散射光线, 是否发生了折射, 以及路径的权重(有薄膜时各颜色分量的反射率不同)
+    pub fn scatter_between(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        n1: f64,
+        n2: f64,
+        mode: &ColorMode,
+    ) -> (Ray, bool, Color) {
+        let refraction_ratio = n1 / n2;
         let unit_direction = r_in.direction().unit_vector();
         let cos_theta = Vec3::dot(&-unit_direction, &rec.normal).min(1.);
         let sin_theta = (1. - cos_theta.powi(2)).sqrt();
 
         let judnot = refraction_ratio * sin_theta > 1.; // 直接是全反射的情况
+        let reflect = Ray::new(
+            rec.p,
+            Vec3::reflect(&unit_direction, &rec.normal),
+            r_in.time(),
+        );
+        let one = Color::new(1., 1., 1.);
+        if judnot {
+            return (reflect, false, one);
+        }
 
-        let dir: Vec3;
-        if judnot || Dielectric::reflectance(cos_theta, refraction_ratio) > random_double() {
+        // 按各分量的平均反射率选择反射或折射, 再用权重修正
+        let reflectance = match (&self.film, mode) {
+            // 直接与真空相邻时查表, 嵌套在其他介质中时才积分
+            (Some((_, tables)), ColorMode::Rgb) if n1 == 1. && n2 == self.ir => {
+                tables[0].value(cos_theta)
+            }
+            (Some((_, tables)), ColorMode::Rgb) if n1 == self.ir && n2 == 1. => {
+                tables[1].value(cos_theta)
+            }
+            (Some((film, _)), _) => film.reflectance_color(cos_theta, n1, n2, mode),
+            (None, _) => one * Dielectric::reflectance(cos_theta, refraction_ratio),
+        };
+        let p = (reflectance.x + reflectance.y + reflectance.z) / 3.;
+
+        if p > random_double() {
             // 比较二者的光强大小决定选哪条射线
-            dir = Vec3::reflect(&unit_direction, &rec.normal);
+            (reflect, false, reflectance / p)
         } else {
-            dir = Vec3::refract(&unit_direction, &rec.normal, refraction_ratio);
+            let dir = Vec3::refract(&unit_direction, &rec.normal, refraction_ratio);
+            (
+                Ray::new(rec.p, dir, r_in.time()),
+                true,
+                (one - reflectance) / (1. - p),
+            )
         }
+    }
+}
+
+impl Material for Dielectric {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        // those kind of material absorbs nothing ! 只有薄膜会改变颜色
+        let (n1, n2) = if rec.front_face {
+            (1., self.ir)
+        } else {
+            (self.ir, 1.)
+        }; // 要判断光线是在光密还是在光疏部分
 
-        // let scattered = Ray::new(rec.p, dir, r_in.time());
+        let (scattered, _, attenuation) = self.scatter_between(r_in, rec, n1, n2, &ColorMode::Rgb);
 
         Some(ScatterRecord {
             attenuation,
             is_specular: true,
-            specular_ray: Ray::new(rec.p, dir, r_in.time()),
+            specular_ray: scattered,
             pdf_ptr: None,
         })
     }
+    fn as_dielectric(&self) -> Option<&Dielectric> {
+        Some(self)
+    }
+}
+
+// 沿路径记录当前所在的电介质(按进入顺序), 用于嵌套电介质的折射率
+#[derive(Clone, Default)]
+pub struct IorStack<'a> {
+    entries: Vec<&'a Dielectric>,
+}
+
+pub enum Crossing<'a> {
+    // 被优先级更高的物体包含, 不是真实的界面, 光线直接穿过
+    Skip(IorStack<'a>),
+    // 真实的界面: 入射侧与透射侧的折射率, 以及发生折射后的栈
+    // dispersive: 两侧折射率是否随波长变化
+    Interface {
+        n1: f64,
+        n2: f64,
+        refracted: IorStack<'a>,
+        dispersive: bool,
+    },
+}
+
+impl<'a> IorStack<'a> {
+    // 优先级最高的物体决定当前所在的介质
+    fn current(&self) -> Option<&'a Dielectric> {
+        let mut best: Option<&'a Dielectric> = None;
+        for d in self.entries.iter().rev() {
+            best = match best {
+                Some(b) if b.priority >= d.priority => Some(b),
+                _ => Some(d),
+            };
+        }
+        best
+    }
+    pub fn ior(&self, lambda: Option<f64>) -> f64 {
+        self.current().map_or(1., |d| d.ior(lambda))
+    }
+    fn dispersive(&self) -> bool {
+        match self.current() {
+            Some(d) => d.dispersive(),
+            None => false,
+        }
+    }
+    pub fn interior(&self) -> Option<&'a Interior> {
+        self.current().and_then(|d| d.interior.as_ref())
+    }
+
+    // 光线在 d 的表面上, front_face 为真表示进入 d; lambda 为光谱模式下的波长
+    pub fn cross(&self, d: &'a Dielectric, front_face: bool, lambda: Option<f64>) -> Crossing<'a> {
+        let current = self.current();
+        let mut next = self.clone();
+        if front_face {
+            next.entries.push(d);
+            match current {
+                Some(c) if c.priority > d.priority => Crossing::Skip(next),
+                _ => Crossing::Interface {
+                    n1: self.ior(lambda),
+                    n2: d.ior(lambda),
+                    dispersive: self.dispersive() || d.dispersive(),
+                    refracted: next,
+                },
+            }
+        } else {
+            if let Some(i) = next.entries.iter().rposition(|e| e.id == d.id) {
+                next.entries.remove(i);
+            }
+            match current {
+                Some(c) if c.id != d.id => Crossing::Skip(next),
+                _ => Crossing::Interface {
+                    n1: d.ior(lambda),
+                    n2: next.ior(lambda),
+                    dispersive: d.dispersive() || next.dispersive(),
+                    refracted: next,
+                },
+            }
+        }
+    }
 }
 
 // reference: https://blog.csdn.net/masilejfoaisegjiae/article/details/104614953
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 返回 (n1, n2, 折射后的栈), Skip 时为None
+    fn interface<'a>(
+        stack: &IorStack<'a>,
+        d: &'a Dielectric,
+        front_face: bool,
+    ) -> Option<(f64, f64, IorStack<'a>)> {
+        match stack.cross(d, front_face, None) {
+            Crossing::Interface {
+                n1, n2, refracted, ..
+            } => Some((n1, n2, refracted)),
+            Crossing::Skip(_) => None,
+        }
+    }
+
+    #[test]
+    fn single_object() {
+        let glass = Dielectric::new(1.5);
+        let (n1, n2, inside) = interface(&IorStack::default(), &glass, true).unwrap();
+        assert_eq!((n1, n2), (1., 1.5));
+        assert_eq!(inside.ior(None), 1.5);
+        let (n1, n2, outside) = interface(&inside, &glass, false).unwrap();
+        assert_eq!((n1, n2), (1.5, 1.));
+        assert_eq!(outside.ior(None), 1.);
+    }
+
+    #[test]
+    fn water_in_glass() {
+        // 杯壁优先级高于水, 重叠部分属于杯壁
+        let glass = Dielectric::new(1.5).with_priority(1);
+        let water = Dielectric::new(1.33);
+
+        let (_, _, in_glass) = interface(&IorStack::default(), &glass, true).unwrap();
+        // 水面在杯壁内部的部分不是真实的界面
+        let in_both = match in_glass.cross(&water, true, None) {
+            Crossing::Skip(next) => next,
+            _ => panic!("water surface inside the glass wall should be skipped"),
+        };
+        assert_eq!(in_both.ior(None), 1.5);
+
+        // 离开杯壁进入水中
+        let (n1, n2, in_water) = interface(&in_both, &glass, false).unwrap();
+        assert_eq!((n1, n2), (1.5, 1.33));
+        // 离开水面回到空气
+        let (n1, n2, outside) = interface(&in_water, &water, false).unwrap();
+        assert_eq!((n1, n2), (1.33, 1.));
+        assert_eq!(outside.ior(None), 1.);
+
+        // 在水中离开杯壁之外的物体: 当前介质是水, 不是真实的界面
+        assert!(interface(&in_water, &glass, false).is_none());
+    }
+
+    #[test]
+    fn equal_priority_prefers_last_entered() {
+        let a = Dielectric::new(1.4);
+        let b = Dielectric::new(1.6);
+        let (_, _, in_a) = interface(&IorStack::default(), &a, true).unwrap();
+        let (n1, n2, in_both) = interface(&in_a, &b, true).unwrap();
+        assert_eq!((n1, n2), (1.4, 1.6));
+        // 离开 a 时当前介质是 b, a 的表面被跳过
+        assert!(interface(&in_both, &a, false).is_none());
+        let (n1, n2, _) = interface(&in_both, &b, false).unwrap();
+        assert_eq!((n1, n2), (1.6, 1.4));
+    }
+
+    #[test]
+    fn clones_are_the_same_object() {
+        let glass = Dielectric::new(1.5);
+        let face = glass.clone();
+        let (_, _, inside) = interface(&IorStack::default(), &glass, true).unwrap();
+        let (_, n2, outside) = interface(&inside, &face, false).unwrap();
+        assert_eq!(n2, 1.);
+        assert_eq!(outside.ior(None), 1.);
+    }
+
+    #[test]
+    fn dispersion() {
+        let flint = Dielectric::new(1.62).with_abbe(36.);
+        assert!(flint.dispersive());
+        assert!(flint.ior(Some(450.)) > flint.ior(Some(650.)));
+        assert!((flint.ior(Some(587.6)) - 1.62).abs() < 1e-9);
+        assert_eq!(flint.ior(None), 1.62);
+        match IorStack::default().cross(&flint, true, Some(500.)) {
+            Crossing::Interface { dispersive, .. } => assert!(dispersive),
+            Crossing::Skip(_) => panic!(),
+        }
+    }
+}
@@ -1,16 +1,87 @@
-use crate::texture::{solid_color::SolidColor, Texture};
+use std::sync::Arc;
 
-use super::{Color, Material};
+use crate::{
+    light::ies::IesProfile,
+    spectrum::{
+        cie::{spectrum_to_xyz, xyz_to_srgb},
+        Spectral, Spectrum,
+    },
+    texture::{solid_color::SolidColor, Texture},
+};
+
+use super::{Bounce, Color, HitRecord, Material, Ray, ONB};
 
 #[derive(Clone)]
 pub struct DiffuseLight<T: Texture> {
     emit: T,
+    intensity: f64,
+    two_sided: bool,              // 单面发光时只有法向一侧(front_face)发光
+    camera_visible: bool,         // 相机能否直接看到光源
+    specular_visible: bool,       // 能否在镜面反射/折射中看到光源
+    ies: Option<Arc<IesProfile>>, // 配光曲线, 灯具轴向为发光一侧的法向
+    // 发光光谱(归一化到亮度为1), 与纹理颜色相乘; RGB 模式下使用其对应的颜色 tint
+    spectrum: Option<(Arc<dyn Spectrum>, f64)>,
+    tint: Color,
 }
 
 impl<T: Texture> DiffuseLight<T> {
+    pub fn new_texture(emit: T) -> Self {
+        Self {
+            emit,
+            intensity: 1.,
+            two_sided: true,
+            camera_visible: true,
+            specular_visible: true,
+            ies: None,
+            spectrum: None,
+            tint: Color::new(1., 1., 1.),
+        }
+    }
     pub fn new(c: Color) -> DiffuseLight<SolidColor> {
-        DiffuseLight {
-            emit: SolidColor::new(c.x, c.y, c.z),
+        DiffuseLight::new_texture(SolidColor::new(c.x, c.y, c.z))
+    }
+
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+    pub fn with_two_sided(mut self, two_sided: bool) -> Self {
+        self.two_sided = two_sided;
+        self
+    }
+    pub fn with_camera_visible(mut self, visible: bool) -> Self {
+        self.camera_visible = visible;
+        self
+    }
+    pub fn with_specular_visible(mut self, visible: bool) -> Self {
+        self.specular_visible = visible;
+        self
+    }
+    pub fn with_ies(mut self, profile: Arc<IesProfile>) -> Self {
+        self.ies = Some(profile);
+        self
+    }
+    // 测量得到的光源光谱, 只取其颜色, 亮度仍由纹理与 intensity 决定
+    pub fn with_spectrum(mut self, spectrum: Arc<dyn Spectrum>) -> Self {
+        let xyz = spectrum_to_xyz(|lambda| spectrum.value(lambda));
+        assert!(xyz.y > 0., "light spectrum has no visible energy");
+        let rgb = xyz_to_srgb(xyz / xyz.y);
+        self.tint = Color::new(rgb.x.max(0.), rgb.y.max(0.), rgb.z.max(0.));
+        self.spectrum = Some((spectrum, 1. / xyz.y));
+        self
+    }
+
+    // 不含光谱颜色的发光
+    fn radiance(&self, r_in: &Ray, rec: &HitRecord) -> Option<Color> {
+        if !self.two_sided && !rec.front_face {
+            return Some(Color::new(0., 0., 0.));
+        }
+        let emitted = self.emit.value(rec.u, rec.v, &rec.p)? * self.intensity;
+        match &self.ies {
+            Some(profile) => {
+                Some(emitted * profile.value(&ONB::build(&rec.normal), &-r_in.direction()))
+            }
+            None => Some(emitted),
         }
     }
 }
@@ -18,7 +89,40 @@ impl<T: Texture> Material for DiffuseLight<T> {
     fn scatter(&self, _r_in: &super::Ray, _rec: &super::HitRecord) -> Option<super::ScatterRecord> {
         None
     }
+    // 发光一侧的亮度, 单双面与配光曲线由 emitted_from 与 emitted_cosine_integral 计入
     fn emitted(&self, u: f64, v: f64, p: &super::Point3) -> Option<super::Color> {
-        self.emit.value(u, v, p) // 其实本质是直接返回一个solidcolor的颜色
+        Some(self.emit.value(u, v, p)? * self.intensity * self.tint) // 其实本质是直接返回一个solidcolor的颜色
+    }
+    fn emitted_from(&self, r_in: &Ray, rec: &HitRecord) -> Option<Color> {
+        Some(self.radiance(r_in, rec)? * self.tint)
+    }
+    fn emitted_spectral(&self, r_in: &Ray, rec: &HitRecord, spectral: &Spectral) -> Option<Color> {
+        let radiance = self.radiance(r_in, rec)?;
+        match &self.spectrum {
+            Some((spectrum, scale)) => {
+                Some(spectral.unbounded(radiance) * spectral.sample(&**spectrum) * *scale)
+            }
+            None => Some(spectral.illuminant(radiance)),
+        }
+    }
+    // 双面发光时两侧各按法向一侧计算
+    fn emitted_cosine_integral(&self) -> f64 {
+        let one_side = match &self.ies {
+            Some(profile) => profile.cosine_integral(),
+            None => std::f64::consts::PI,
+        };
+        if self.two_sided {
+            2. * one_side
+        } else {
+            one_side
+        }
+    }
+    fn visible_to(&self, bounce: Bounce) -> bool {
+        match bounce {
+            Bounce::Camera => self.camera_visible,
+            Bounce::Specular => self.specular_visible,
+            // 与光源采样做MIS的路径必须能看到光源
+            Bounce::Diffuse(_) => true,
+        }
     }
 }
@@ -29,7 +29,7 @@ impl<T: Texture> Material for Lambertian<T> {
             is_specular: false,
             specular_ray: Ray::default(),
             attenuation: self.albedo.value(rec.u, rec.v, &rec.p).unwrap(),
-            pdf_ptr: Some(CosPDF::new(&rec.normal)),
+            pdf_ptr: Some(Box::new(CosPDF::new(&rec.normal))),
         })
     }
     fn scatter_pdf(&self, _r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Option<f64> {
@@ -1,7 +1,13 @@
-use super::{Color, HitRecord, Material, Ray, ScatterRecord, Vec3};
+use super::{
+    thin_film::{FilmTable, ThinFilm},
+    Color, HitRecord, Material, Ray, ScatterRecord, Vec3,
+};
+use crate::spectrum::{ColorMode, Spectral};
 
 #[derive(Clone, Copy)]
 pub struct Metal {
+    // 表面的氧化层等薄膜, 产生随角度变化的干涉色; 附带 RGB 模式的查找表
+    film: Option<(ThinFilm, &'static FilmTable)>,
     pub albedo: Color, // 材质本身的反射率
     pub fuzz: f64,     // 带有哑光效果, fuzz=0表示材质表面为理想金属, 即反射角严格等于入射角
                        // fuzz越大, 反射角和入射角的差异会越大
@@ -12,15 +18,27 @@ impl Metal {
         Self {
             albedo: al,
             fuzz: if fuz < 1. { fuz } else { 1. },
+            film: None,
         }
     }
-}
+    pub fn with_thin_film(mut self, film: ThinFilm) -> Self {
+        // 材质一直存在到渲染结束, 查找表直接泄漏以保持 Metal 为 Copy
+        let table = Box::leak(Box::new(film.metal_rgb_table(self.albedo)));
+        self.film = Some((film, table));
+        self
+    }
 
-impl Material for Metal {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
-        let reflected = Vec3::reflect(&r_in.direction().unit_vector(), &rec.normal);
-        Some(ScatterRecord {
-            attenuation: self.albedo,
+    fn reflect(&self, r_in: &Ray, rec: &HitRecord, mode: &ColorMode) -> ScatterRecord {
+        let unit_direction = r_in.direction().unit_vector();
+        let reflected = Vec3::reflect(&unit_direction, &rec.normal);
+        let cos_theta = Vec3::dot(&-unit_direction, &rec.normal).clamp(0., 1.);
+        let attenuation = match (&self.film, mode) {
+            (Some((_, table)), ColorMode::Rgb) => table.value(cos_theta),
+            (Some((film, _)), _) => film.metal_reflectance(cos_theta, self.albedo, mode),
+            (None, _) => mode.albedo(self.albedo),
+        };
+        ScatterRecord {
+            attenuation,
             is_specular: true,
             specular_ray: Ray::new(
                 rec.p,
@@ -28,6 +46,21 @@ impl Material for Metal {
                 0.,
             ),
             pdf_ptr: None,
-        })
+        }
+    }
+}
+
+impl Material for Metal {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        Some(self.reflect(r_in, rec, &ColorMode::Rgb))
+    }
+    // 薄膜的反射率直接在各采样波长处计算
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        spectral: &Spectral,
+    ) -> Option<ScatterRecord> {
+        Some(self.reflect(r_in, rec, &ColorMode::Spectral(*spectral)))
     }
 }
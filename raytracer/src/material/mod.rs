@@ -1,10 +1,12 @@
+pub mod coated;
 pub mod dielectric;
 pub mod diffuse;
 pub mod isotropic;
 pub mod lambertian;
 pub mod matel;
+pub mod thin_film;
+pub mod volume;
 
-use crate::pdf::cospdf::CosPDF;
 pub use crate::{
     basic::{
         RAY::Ray,
@@ -12,6 +14,7 @@ pub use crate::{
     },
     Hit::HitRecord,
 };
+use crate::{pdf::PDF, spectrum::Spectral};
 
 pub struct ScatterRecord {
     // 1 - attenuation := 光线的被吸收量, attenuation在某种意义上和材料的albedo(反射率)等价
@@ -20,9 +23,10 @@ pub struct ScatterRecord {
     pub attenuation: Color,
     pub is_specular: bool,
     pub specular_ray: Ray,
-    pub pdf_ptr: Option<CosPDF>,
+    pub pdf_ptr: Option<Box<dyn PDF>>,
 }
 
+#[derive(Clone, Copy)]
 pub struct ONB {
     // 一组正交基
     pub axis: [Vec3; 3],
@@ -58,14 +62,68 @@ impl ONB {
     }
 }
 
+// 光线来自哪种顶点, 决定击中发光物体时是否可见以及MIS权重
+#[derive(Clone, Copy)]
+pub enum Bounce {
+    Camera,
+    Specular,
+    Diffuse(f64), // 上一个顶点已做过光源采样, 击中光源时的MIS权重
+}
+
+impl Bounce {
+    pub fn emission_weight(&self) -> f64 {
+        match self {
+            Bounce::Diffuse(weight) => *weight,
+            _ => 1.,
+        }
+    }
+}
+
 pub trait Material: Send + Sync {
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord>;
+    // 光谱模式下的散射, attenuation 为各采样波长处的值; 默认由 RGB 的 attenuation 转换
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        spectral: &Spectral,
+    ) -> Option<ScatterRecord> {
+        let mut srec = self.scatter(r_in, rec)?;
+        srec.attenuation = spectral.albedo(srec.attenuation);
+        Some(srec)
+    }
 
     fn scatter_pdf(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> Option<f64> {
         None
     }
+    // 随散射方向变化的颜色(如涂层的吸收), 与 attenuation 和 scatter_pdf 相乘
+    fn scatter_tint(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> Color {
+        Color::new(1., 1., 1.)
+    }
 
     fn emitted(&self, _u: f64, _v: f64, _p: &Point3) -> Option<Color> {
         Some(Color::new(0., 0., 0.))
     }
+    // 光线 r_in 击中 rec 时看到的发光, 可以区分正反面与出射方向
+    fn emitted_from(&self, _r_in: &Ray, rec: &HitRecord) -> Option<Color> {
+        self.emitted(rec.u, rec.v, &rec.p)
+    }
+    // 光谱模式下在各采样波长处的发光, 默认由 RGB 发光转换
+    fn emitted_spectral(&self, r_in: &Ray, rec: &HitRecord, spectral: &Spectral) -> Option<Color> {
+        Some(spectral.illuminant(self.emitted_from(r_in, rec)?))
+    }
+    // 发光亮度在所有出射方向上对 cos 的积分(相对于 emitted), 用于估计面光源功率
+    // 单面 Lambert 发光为 PI, 双面为 2 PI
+    fn emitted_cosine_integral(&self) -> f64 {
+        std::f64::consts::PI
+    }
+    // 对这类光线不可见的物体被光线直接穿过
+    fn visible_to(&self, _bounce: Bounce) -> bool {
+        true
+    }
+
+    // 电介质在积分器中按嵌套规则处理
+    fn as_dielectric(&self) -> Option<&dielectric::Dielectric> {
+        None
+    }
 }
@@ -0,0 +1,173 @@
+use std::{
+    f64::consts::PI,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use super::Color;
+use crate::spectrum::ColorMode;
+
+// 复数, 用于吸收介质与倏逝波(全反射)下的 Fresnel 系数
+#[derive(Clone, Copy)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+    fn real(re: f64) -> Self {
+        Self { re, im: 0. }
+    }
+    fn norm_sqr(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+    // 主值, 实部非负
+    fn sqrt(self) -> Self {
+        let r = self.norm_sqr().sqrt();
+        let re = (0.5 * (r + self.re)).max(0.).sqrt();
+        let im = (0.5 * (r - self.re)).max(0.).sqrt();
+        Self::new(re, if self.im < 0. { -im } else { im })
+    }
+    fn exp(self) -> Self {
+        let m = self.re.exp();
+        Self::new(m * self.im.cos(), m * self.im.sin())
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+impl Sub for Complex {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+impl Div for Complex {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let d = rhs.norm_sqr();
+        Self::new(
+            (self.re * rhs.re + self.im * rhs.im) / d,
+            (self.im * rhs.re - self.re * rhs.im) / d,
+        )
+    }
+}
+
+// 两种偏振下界面的振幅反射系数
+fn fresnel_s(ni: Complex, ci: Complex, nj: Complex, cj: Complex) -> Complex {
+    (ni * ci - nj * cj) / (ni * ci + nj * cj)
+}
+fn fresnel_p(ni: Complex, ci: Complex, nj: Complex, cj: Complex) -> Complex {
+    (nj * ci - ni * cj) / (nj * ci + ni * cj)
+}
+
+// RGB 模式下反射率随 cos_theta 的查找表: 对可见光谱积分需要几十次求值, 在构造材质时预先计算
+const TABLE_SIZE: usize = 64;
+
+#[derive(Clone)]
+pub struct FilmTable {
+    values: Vec<Color>,
+}
+
+impl FilmTable {
+    fn new<F: Fn(f64) -> Color>(f: F) -> Self {
+        Self {
+            values: (0..=TABLE_SIZE)
+                .map(|i| f(i as f64 / TABLE_SIZE as f64))
+                .collect(),
+        }
+    }
+    pub fn value(&self, cos_theta: f64) -> Color {
+        let x = cos_theta.clamp(0., 1.) * TABLE_SIZE as f64;
+        let i = (x as usize).min(TABLE_SIZE - 1);
+        let t = x - i as f64;
+        self.values[i] * (1. - t) + self.values[i + 1] * t
+    }
+}
+
+// 表面上的一层透明薄膜(肥皂泡, 油膜, 金属氧化层): 膜上下表面的反射光相干叠加
+// 颜色随膜厚与观察角度变化
+// reference: https://en.wikipedia.org/wiki/Thin-film_interference
+#[derive(Clone, Copy)]
+pub struct ThinFilm {
+    pub thickness: f64, // 膜厚(nm), 几百nm时干涉色最明显
+    pub ior: f64,
+}
+
+impl ThinFilm {
+    // 例: 肥皂膜 300nm / 1.33, 水面上的油膜 400nm / 1.5, 钛的氧化层 100nm / 2.4
+    pub fn new(thickness: f64, ior: f64) -> Self {
+        Self { thickness, ior }
+    }
+
+    // 从折射率 n1 的介质以 cos_theta 入射, 经过薄膜到达折射率为 n2 的基底, 波长 lambda 处的反射率(两种偏振平均)
+    fn airy(&self, cos_theta: f64, n1: f64, n2: Complex, lambda: f64) -> f64 {
+        let one = Complex::real(1.);
+        let n0 = Complex::real(n1);
+        let nf = Complex::real(self.ior);
+        // 斯涅尔定律: n sinθ 守恒
+        let k = n1 * n1 * (1. - cos_theta * cos_theta);
+        let cos_in = |n: Complex| (one - Complex::real(k) / (n * n)).sqrt();
+        let c0 = Complex::real(cos_theta);
+        let cf = cos_in(nf);
+        let c2 = cos_in(n2);
+
+        // 在膜内往返一次的相位差
+        let phase = (Complex::new(0., 4. * PI * self.thickness / lambda) * nf * cf).exp();
+        let airy = |r01: Complex, r12: Complex| {
+            ((r01 + r12 * phase) / (one + r01 * r12 * phase)).norm_sqr()
+        };
+        let rs = airy(fresnel_s(n0, c0, nf, cf), fresnel_s(nf, cf, n2, c2));
+        let rp = airy(fresnel_p(n0, c0, nf, cf), fresnel_p(nf, cf, n2, c2));
+        (0.5 * (rs + rp)).clamp(0., 1.)
+    }
+
+    // 透明基底(折射率 n2)在波长 lambda 处的反射率
+    pub fn reflectance(&self, cos_theta: f64, n1: f64, n2: f64, lambda: f64) -> f64 {
+        self.airy(cos_theta, n1, Complex::real(n2), lambda)
+    }
+    // 当前颜色模式下的反射率: RGB 模式下对可见光谱积分, 光谱模式下取各采样波长
+    pub fn reflectance_color(&self, cos_theta: f64, n1: f64, n2: f64, mode: &ColorMode) -> Color {
+        mode.reflectance(&|lambda| self.reflectance(cos_theta, n1, n2, lambda))
+    }
+    pub fn rgb_table(&self, n1: f64, n2: f64) -> FilmTable {
+        FilmTable::new(|cos_theta| self.reflectance_color(cos_theta, n1, n2, &ColorMode::Rgb))
+    }
+
+    // 金属基底: 由垂直入射时的反射率得到等效的复折射率, 颜色仍由 albedo 决定
+    // reference: Gulbrandsen. Artist Friendly Metallic Fresnel. 2014
+    pub fn metal_reflectance(&self, cos_theta: f64, albedo: Color, mode: &ColorMode) -> Color {
+        let r = albedo.luminance().clamp(1e-3, 0.999);
+        let n = r * (1. - r) / (1. + r) + (1. - r) * (1. + r.sqrt()) / (1. - r.sqrt());
+        let k = ((r * (n + 1.).powi(2) - (n - 1.).powi(2)) / (1. - r))
+            .max(0.)
+            .sqrt();
+        let substrate = Complex::new(n, k);
+
+        let film = mode.reflectance(&|lambda| self.airy(cos_theta, 1., substrate, lambda));
+        let tinted = film * mode.albedo(albedo) / r;
+        Color::new(
+            tinted.x.clamp(0., 1.),
+            tinted.y.clamp(0., 1.),
+            tinted.z.clamp(0., 1.),
+        )
+    }
+    pub fn metal_rgb_table(&self, albedo: Color) -> FilmTable {
+        FilmTable::new(|cos_theta| self.metal_reflectance(cos_theta, albedo, &ColorMode::Rgb))
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{
+    pdf::phase::{PhaseFunction, PhasePDF},
+    texture::Texture,
+};
+
+use super::{HitRecord, Material, Ray, ScatterRecord, Vec3};
+
+// 介质内部的散射: albedo 决定颜色, phase 决定散射方向的分布
+#[derive(Clone)]
+pub struct Volume<T: Texture, P: PhaseFunction + Copy + 'static> {
+    pub albedo: T,
+    pub phase: P,
+}
+
+impl<T: Texture, P: PhaseFunction + Copy + 'static> Volume<T, P> {
+    pub fn with_phase(albedo: T, phase: P) -> Self {
+        Self { albedo, phase }
+    }
+}
+
+impl<T: Texture, P: PhaseFunction + Copy + 'static> Material for Volume<T, P> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        Some(ScatterRecord {
+            attenuation: self.albedo.value(rec.u, rec.v, &rec.p).unwrap(),
+            is_specular: false,
+            specular_ray: Ray::default(),
+            pdf_ptr: Some(Box::new(PhasePDF::new(self.phase, &r_in.direction()))),
+        })
+    }
+    fn scatter_pdf(&self, r_in: &Ray, _rec: &HitRecord, scattered: &Ray) -> Option<f64> {
+        let cos = Vec3::dot(
+            &r_in.direction().unit_vector(),
+            &scattered.direction().unit_vector(),
+        );
+        Some(self.phase.value(cos))
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{
+    basic::animation::AnimatedTransform,
+    bvh::{aabb::AABB, stats::BvhStats},
+    Hit::{HitRecord, Hittable, Point3, Ray, Vec3},
+};
+
+// 求运动包围盒时在快门区间内的采样数
+const MOTION_SAMPLES: usize = 32;
+
+// 随时间变化的变换(关键帧动画), 按光线的时间取变换, 从而产生运动模糊
+pub struct Animated<H: Hittable> {
+    ptr: H,
+    animation: AnimatedTransform,
+}
+
+impl<H: Hittable> Animated<H> {
+    pub fn new(ptr: H, animation: AnimatedTransform) -> Self {
+        Self { ptr, animation }
+    }
+}
+
+impl<H: Hittable> Hittable for Animated<H> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let transform = self.animation.at(r.time());
+        if let Some(mut rec) = self.ptr.hit(&transform.inverse().ray(r), t_min, t_max) {
+            rec.p = transform.point(&rec.p);
+            rec.normal = transform.normal(&rec.normal).unit_vector();
+            Some(rec)
+        } else {
+            None
+        }
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        let transform = self.animation.at(r.time());
+        self.ptr.occluded(&transform.inverse().ray(r), t_min, t_max)
+    }
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let transform = self.animation.at(r.time());
+        self.ptr
+            .transmittance(&transform.inverse().ray(r), t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
+        let bbox = self.ptr.bounding_box(time0, time1)?;
+
+        // 在区间内均匀采样, 并加入区间内的关键帧时刻
+        let mut times: Vec<f64> = (0..=MOTION_SAMPLES)
+            .map(|i| time0 + (time1 - time0) * i as f64 / MOTION_SAMPLES as f64)
+            .collect();
+        times.extend(
+            self.animation
+                .keys()
+                .iter()
+                .map(|key| key.time)
+                .filter(|&t| t > time0 && t < time1),
+        );
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let mut mi = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut mx = -mi;
+        let mut pad: f64 = 0.;
+        let mut last_corners: Option<Vec<Point3>> = None;
+
+        for time in times {
+            let transform = self.animation.at(time);
+            let mut corners = Vec::with_capacity(8);
+            for i in 0..8 {
+                let corner = Point3::new(
+                    if i & 1 == 0 { bbox.mini.x } else { bbox.maxi.x },
+                    if i & 2 == 0 { bbox.mini.y } else { bbox.maxi.y },
+                    if i & 4 == 0 { bbox.mini.z } else { bbox.maxi.z },
+                );
+                let p = transform.point(&corner);
+                for c in 0..3 {
+                    mi[c] = mi[c].min(p[c]);
+                    mx[c] = mx[c].max(p[c]);
+                }
+                corners.push(p);
+            }
+            // 两个采样时刻之间顶点走的是曲线(旋转), 用半个弦长作为保守的外扩量
+            if let Some(last) = &last_corners {
+                for (p, q) in corners.iter().zip(last.iter()) {
+                    pad = pad.max((*p - *q).len() / 2.);
+                }
+            }
+            last_corners = Some(corners);
+        }
+
+        let pad = Vec3::new(pad, pad, pad);
+        Some(AABB::new(mi - pad, mx + pad))
+    }
+
+    // pdf_value/random 没有时间参数, 与其他物体一样按 time = 0 处理
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        let transform = self.animation.at(0.);
+        let local_o = transform.m_inv.point(o);
+        let (local_v, jacobian) = transform.local_direction(v);
+        self.ptr.pdf_value(&local_o, &local_v) * jacobian
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        let transform = self.animation.at(0.);
+        transform.vector(&self.ptr.random(&transform.m_inv.point(o)))
+    }
+    fn light_samplable(&self) -> bool {
+        self.ptr.light_samplable()
+    }
+    // 发光亮度不变, 功率随 time = 0 时的表面积缩放
+    fn power(&self) -> Option<f64> {
+        Some(self.ptr.power()? * self.animation.at(0.).area_scale())
+    }
+
+    fn refit(&mut self, time0: f64, time1: f64) {
+        self.ptr.refit(time0, time1);
+    }
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        self.ptr.sah_cost(time0, time1)
+    }
+    fn bvh_stats(&self, depth: usize, stats: &mut BvhStats) -> bool {
+        stats.memory += std::mem::size_of::<Self>() - std::mem::size_of::<H>();
+        self.ptr.bvh_stats(depth, stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        basic::animation::{Interpolation, Keyframe, Quaternion},
+        material::lambertian::Lambertian,
+        object::cube::Cube,
+        texture::solid_color::SolidColor,
+        Hit::Color,
+    };
+
+    fn spinning_cube() -> Animated<Cube> {
+        let cube = Cube::new(
+            Point3::new(-1., -1., -1.),
+            Point3::new(1., 1., 1.),
+            Lambertian::<SolidColor>::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        let animation = AnimatedTransform::new(
+            vec![
+                Keyframe::at(0., Vec3::new(0., 0., 0.)),
+                Keyframe::new(
+                    1.,
+                    Vec3::new(5., 0., 0.),
+                    Quaternion::from_axis_angle(Vec3::new(0., 1., 0.), 170.),
+                    Vec3::new(2., 2., 2.),
+                ),
+            ],
+            Interpolation::Linear,
+        );
+        Animated::new(cube, animation)
+    }
+
+    #[test]
+    fn bounding_box_covers_shutter_interval() {
+        let animated = spinning_cube();
+        let (time0, time1) = (0.2, 0.9);
+        let bbox = animated.bounding_box(time0, time1).unwrap();
+        for step in 0..=1000 {
+            let time = time0 + (time1 - time0) * step as f64 / 1000.;
+            let transform = animated.animation.at(time);
+            for i in 0..8 {
+                let corner = Point3::new(
+                    if i & 1 == 0 { -1. } else { 1. },
+                    if i & 2 == 0 { -1. } else { 1. },
+                    if i & 4 == 0 { -1. } else { 1. },
+                );
+                let p = transform.point(&corner);
+                for c in 0..3 {
+                    assert!(bbox.mini[c] <= p[c] && p[c] <= bbox.maxi[c], "t = {}", time);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hit_follows_time() {
+        let animated = spinning_cube();
+        let r0 = Ray::new(Point3::new(0., 0., -10.), Vec3::new(0., 0., 1.), 0.);
+        let r1 = Ray::new(Point3::new(0., 0., -10.), Vec3::new(0., 0., 1.), 1.);
+        assert!(animated.hit(&r0, 0.001, f64::INFINITY).is_some());
+        assert!(animated.hit(&r1, 0.001, f64::INFINITY).is_none());
+
+        let r = Ray::new(Point3::new(5., 0., -10.), Vec3::new(0., 0., 1.), 1.);
+        let rec = animated.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!((rec.normal.len() - 1.).abs() < 1e-9);
+        assert!(rec.p.z < -1.);
+    }
+}
@@ -0,0 +1,148 @@
+use std::f64::consts::PI;
+
+use crate::{
+    basic::random_double,
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    light::area_power,
+    Hit::{HitRecord, Hittable, Material, Point3, Ray, Vec3},
+};
+
+use super::quadric::{area_pdf_value, azimuth, solve_quadratic, Roots};
+
+const SIDE: usize = 0;
+const BOTTOM: usize = 1;
+const TOP: usize = 2;
+
+// 胶囊体: 线段 center -> center + (0, height, 0) 周围半径 radius 的点集
+pub struct Capsule<M: Material> {
+    pub center: Point3,
+    pub radius: f64,
+    pub height: f64,
+    pub mat: M,
+}
+
+impl<M: Material> Capsule<M> {
+    pub fn new(center: Point3, radius: f64, height: f64, mat: M) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            mat,
+        }
+    }
+
+    fn roots(&self, r: &Ray) -> Roots {
+        let o = r.origin() - self.center;
+        let d = r.direction();
+        let mut roots = Roots::default();
+
+        // 圆柱侧面
+        let a = d.x() * d.x() + d.z() * d.z();
+        let half_b = o.x() * d.x() + o.z() * d.z();
+        let c = o.x() * o.x() + o.z() * o.z() - self.radius * self.radius;
+        if a > 0. {
+            for t in solve_quadratic(a, half_b, c) {
+                let y = o.y() + t * d.y();
+                if y >= 0. && y <= self.height {
+                    roots.push(t, SIDE);
+                }
+            }
+        }
+
+        // 两端的半球
+        for &(k, part) in [(0., BOTTOM), (self.height, TOP)].iter() {
+            let oc = o - Vec3::new(0., k, 0.);
+            let half_b = Vec3::dot(&oc, &d);
+            let c = oc.len_square() - self.radius * self.radius;
+            for t in solve_quadratic(d.len_square(), half_b, c) {
+                let y = o.y() + t * d.y();
+                if (part == BOTTOM && y < 0.) || (part == TOP && y > self.height) {
+                    roots.push(t, part);
+                }
+            }
+        }
+        roots
+    }
+    fn surface(&self, p: &Vec3, part: usize) -> (Vec3, f64, f64) {
+        // v 按经线弧长从底部到顶部映射到[0, 1]
+        let quarter = PI / 2. * self.radius;
+        let length = 2. * quarter + self.height;
+        let (axis, s) = match part {
+            SIDE => (Vec3::new(0., p.y(), 0.), quarter + p.y()),
+            BOTTOM => (
+                Vec3::default(),
+                self.radius * (-p.y() / self.radius).min(1.).acos(),
+            ),
+            _ => {
+                let y = p.y() - self.height;
+                (
+                    Vec3::new(0., self.height, 0.),
+                    quarter + self.height + self.radius * (y / self.radius).min(1.).asin(),
+                )
+            }
+        };
+        ((*p - axis) / self.radius, azimuth(p), s / length)
+    }
+    fn area(&self) -> f64 {
+        4. * PI * self.radius * self.radius + 2. * PI * self.radius * self.height
+    }
+}
+
+impl<M: Material> Hittable for Capsule<M> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
+        let (t, part) = self.roots(r).nearest(t_min, t_max)?;
+        let p = r.at(t);
+        let (outward_normal, u, v) = self.surface(&(p - self.center), part);
+
+        let mut rec = HitRecord::new(t, p, Vec3::default(), bool::default(), &self.mat, u, v);
+        rec.set_face_normal(r, &outward_normal);
+        Some(rec)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        self.roots(r).any(t_min, t_max)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        Some(AABB::new(
+            self.center - Vec3::new(self.radius, self.radius, self.radius),
+            self.center + Vec3::new(self.radius, self.height + self.radius, self.radius),
+        ))
+    }
+
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        let r = Ray::new(*o, *v, 0.);
+        area_pdf_value(&self.roots(&r), &r, self.area(), |p, part| {
+            self.surface(&(*p - self.center), part).0
+        })
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        let phi = 2. * PI * random_double();
+        let side = 2. * PI * self.radius * self.height;
+        let p = if random_double() * self.area() < side {
+            Vec3::new(
+                self.radius * phi.cos(),
+                self.height * random_double(),
+                self.radius * phi.sin(),
+            )
+        } else {
+            // 球面上均匀采样, 上半球平移到顶端
+            let y = 1. - 2. * random_double();
+            let rho = (1. - y * y).sqrt();
+            let offset = if y > 0. { self.height } else { 0. };
+            Vec3::new(
+                self.radius * rho * phi.cos(),
+                self.radius * y + offset,
+                self.radius * rho * phi.sin(),
+            )
+        };
+        self.center + p - *o
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    fn power(&self) -> Option<f64> {
+        area_power(&self.mat, &self.center, self.area())
+    }
+}
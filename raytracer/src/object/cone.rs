@@ -0,0 +1,141 @@
+use std::f64::consts::PI;
+
+use crate::{
+    basic::random_double,
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    light::area_power,
+    Hit::{HitRecord, Hittable, Material, Point3, Ray, Vec3},
+};
+
+use super::quadric::{area_pdf_value, azimuth, solve_quadratic, Roots};
+
+const SIDE: usize = 0;
+const BOTTOM: usize = 1;
+
+// 圆锥: 底面圆心 center, 底面半径 radius, 顶点在 center + (0, height, 0)
+pub struct Cone<M: Material> {
+    pub center: Point3,
+    pub radius: f64,
+    pub height: f64,
+    pub capped: bool, // 是否有底面
+    pub mat: M,
+}
+
+impl<M: Material> Cone<M> {
+    pub fn new(center: Point3, radius: f64, height: f64, capped: bool, mat: M) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            capped,
+            mat,
+        }
+    }
+
+    fn roots(&self, r: &Ray) -> Roots {
+        let o = r.origin() - self.center;
+        let d = r.direction();
+        let mut roots = Roots::default();
+
+        // x^2 + z^2 = k^2 (h - y)^2
+        let k2 = (self.radius / self.height).powi(2);
+        let h = self.height - o.y();
+        let a = d.x() * d.x() + d.z() * d.z() - k2 * d.y() * d.y();
+        let half_b = o.x() * d.x() + o.z() * d.z() + k2 * h * d.y();
+        let c = o.x() * o.x() + o.z() * o.z() - k2 * h * h;
+        for t in solve_quadratic(a, half_b, c) {
+            let y = o.y() + t * d.y();
+            if y >= 0. && y <= self.height {
+                roots.push(t, SIDE);
+            }
+        }
+
+        if self.capped && d.y() != 0. {
+            let t = -o.y() / d.y();
+            let x = o.x() + t * d.x();
+            let z = o.z() + t * d.z();
+            if x * x + z * z <= self.radius * self.radius {
+                roots.push(t, BOTTOM);
+            }
+        }
+        roots
+    }
+    fn surface(&self, p: &Vec3, part: usize) -> (Vec3, f64, f64) {
+        if part == BOTTOM {
+            let rho = (p.x() * p.x() + p.z() * p.z()).sqrt();
+            return (Vec3::new(0., -1., 0.), azimuth(p), rho / self.radius);
+        }
+        let k2 = (self.radius / self.height).powi(2);
+        let n = Vec3::new(p.x(), k2 * (self.height - p.y()), p.z());
+        // 顶点处法向退化, 取 +y
+        let n = if n.len_square() < 1e-12 {
+            Vec3::new(0., 1., 0.)
+        } else {
+            n.unit_vector()
+        };
+        (n, azimuth(p), p.y() / self.height)
+    }
+    fn side_area(&self) -> f64 {
+        PI * self.radius * (self.radius.powi(2) + self.height.powi(2)).sqrt()
+    }
+    fn area(&self) -> f64 {
+        if self.capped {
+            self.side_area() + PI * self.radius * self.radius
+        } else {
+            self.side_area()
+        }
+    }
+}
+
+impl<M: Material> Hittable for Cone<M> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
+        let (t, part) = self.roots(r).nearest(t_min, t_max)?;
+        let p = r.at(t);
+        let (outward_normal, u, v) = self.surface(&(p - self.center), part);
+
+        let mut rec = HitRecord::new(t, p, Vec3::default(), bool::default(), &self.mat, u, v);
+        rec.set_face_normal(r, &outward_normal);
+        Some(rec)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        self.roots(r).any(t_min, t_max)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        Some(AABB::new(
+            self.center + Vec3::new(-self.radius, 0., -self.radius),
+            self.center + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        let r = Ray::new(*o, *v, 0.);
+        area_pdf_value(&self.roots(&r), &r, self.area(), |p, part| {
+            self.surface(&(*p - self.center), part).0
+        })
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        let phi = 2. * PI * random_double();
+        let p = if random_double() * self.area() < self.side_area() {
+            // 侧面上到顶点的距离比例 s 的密度正比于 s
+            let s = random_double().sqrt();
+            Vec3::new(
+                s * self.radius * phi.cos(),
+                (1. - s) * self.height,
+                s * self.radius * phi.sin(),
+            )
+        } else {
+            let rho = self.radius * random_double().sqrt();
+            Vec3::new(rho * phi.cos(), 0., rho * phi.sin())
+        };
+        self.center + p - *o
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    fn power(&self) -> Option<f64> {
+        area_power(&self.mat, &self.center, self.area())
+    }
+}
@@ -0,0 +1,346 @@
+use std::f64::INFINITY;
+
+use crate::{
+    bvh::{
+        aabb::{surrounding_box, AABB},
+        stats::BvhStats,
+    },
+    Hit::{HitRecord, Hittable, Point3, Ray, Vec3},
+};
+
+// 光线穿过实体的一段: 从进入点到离开点
+#[derive(Clone, Copy)]
+pub struct Interval<'a> {
+    pub enter: HitRecord<'a>,
+    pub exit: HitRecord<'a>,
+}
+
+// 封闭的实体, 可以参与CSG运算
+pub trait Solid: Hittable {
+    // 光线所在直线(t 可为负)穿过实体且与 [t_min, t_max] 相交的区间, 按 t 排序且互不相交
+    // 区间的端点可以在 [t_min, t_max] 之外, 范围之外的端点只保证先后顺序正确
+    // 进入点 front_face = true, 离开点 front_face = false, 法向均与光线方向相反
+    fn intervals(&self, r: &Ray, t_min: f64, t_max: f64) -> Vec<Interval>;
+}
+
+// 从 t_min 开始沿光线依次求出封闭曲面的交点, 按进出配对; t_max 之后的进入点不再需要
+// 第一个交点为离开点时 t_min 已在内部, 进入点记在 t = -INFINITY 处
+pub fn surface_intervals<'a, H: Hittable + ?Sized>(
+    surface: &'a H,
+    r: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Vec<Interval<'a>> {
+    let mut intervals = Vec::new();
+    let mut enter = None;
+    let mut t = t_min;
+    let mut first = true;
+    while let Some(rec) = surface.hit(r, t, INFINITY) {
+        // 略过同一位置的重复交点(如三角形的公共边)
+        t = rec.t + 1e-7 * (1. + rec.t.abs());
+        match (rec.front_face, enter) {
+            (true, None) => {
+                if rec.t > t_max {
+                    break;
+                }
+                enter = Some(rec);
+            }
+            (false, Some(enter_rec)) => {
+                intervals.push(Interval {
+                    enter: enter_rec,
+                    exit: rec,
+                });
+                enter = None;
+            }
+            (false, None) if first => intervals.push(Interval {
+                enter: HitRecord {
+                    t: -INFINITY,
+                    front_face: true,
+                    ..rec
+                },
+                exit: rec,
+            }),
+            _ => {}
+        }
+        first = false;
+    }
+    intervals
+}
+
+// 把封闭的网格(或其他封闭曲面)当作实体
+pub struct Closed<H: Hittable> {
+    ptr: H,
+}
+
+impl<H: Hittable> Closed<H> {
+    pub fn new(ptr: H) -> Self {
+        Self { ptr }
+    }
+}
+
+impl<H: Hittable> Hittable for Closed<H> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.ptr.hit(r, t_min, t_max)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.ptr.occluded(r, t_min, t_max)
+    }
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
+        self.ptr.bounding_box(time0, time1)
+    }
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        self.ptr.pdf_value(o, v)
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        self.ptr.random(o)
+    }
+    fn light_samplable(&self) -> bool {
+        self.ptr.light_samplable()
+    }
+    fn refit(&mut self, time0: f64, time1: f64) {
+        self.ptr.refit(time0, time1);
+    }
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        self.ptr.sah_cost(time0, time1)
+    }
+    fn bvh_stats(&self, depth: usize, stats: &mut BvhStats) -> bool {
+        self.ptr.bvh_stats(depth, stats)
+    }
+}
+
+impl<H: Hittable> Solid for Closed<H> {
+    fn intervals(&self, r: &Ray, t_min: f64, t_max: f64) -> Vec<Interval> {
+        surface_intervals(&self.ptr, r, t_min, t_max)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    Difference, // a - b
+}
+
+impl CsgOp {
+    fn inside(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            CsgOp::Union => in_a || in_b,
+            CsgOp::Intersection => in_a && in_b,
+            CsgOp::Difference => in_a && !in_b,
+        }
+    }
+}
+
+// 两个实体的布尔运算, 结果仍是实体, 可以继续嵌套
+// 边界上的法向和材质取自该处所在的原物体表面
+pub struct Csg<A: Solid, B: Solid> {
+    pub a: A,
+    pub b: B,
+    pub op: CsgOp,
+}
+
+impl<A: Solid, B: Solid> Csg<A, B> {
+    pub fn new(a: A, b: B, op: CsgOp) -> Self {
+        Self { a, b, op }
+    }
+}
+
+impl<A: Solid, B: Solid> Solid for Csg<A, B> {
+    // 只有 [t_min, t_max] 内的结果是准确的: 范围之外的进出事件可能被省略
+    fn intervals(&self, r: &Ray, t_min: f64, t_max: f64) -> Vec<Interval> {
+        // 一侧为空时结果直接可知
+        let intervals_a = self.a.intervals(r, t_min, t_max);
+        if intervals_a.is_empty() && self.op != CsgOp::Union {
+            return intervals_a;
+        }
+        let intervals_b = self.b.intervals(r, t_min, t_max);
+        if intervals_b.is_empty() {
+            return match self.op {
+                CsgOp::Intersection => intervals_b,
+                _ => intervals_a,
+            };
+        }
+        if intervals_a.is_empty() {
+            return intervals_b;
+        }
+
+        // 所有进出事件: (交点, 是否来自a, 是否进入)
+        let mut events = Vec::new();
+        for i in intervals_a {
+            events.push((i.enter, true, true));
+            events.push((i.exit, true, false));
+        }
+        for i in intervals_b {
+            events.push((i.enter, false, true));
+            events.push((i.exit, false, false));
+        }
+        events.sort_by(|x, y| x.0.t.partial_cmp(&y.0.t).unwrap());
+
+        let mut intervals = Vec::new();
+        let (mut in_a, mut in_b, mut inside) = (false, false, false);
+        let mut enter = None;
+        for (mut rec, from_a, entering) in events {
+            if from_a {
+                in_a = entering;
+            } else {
+                in_b = entering;
+            }
+            let now = self.op.inside(in_a, in_b);
+            // 法向总是与光线相反, 只需改写 front_face (如 b 的离开点变为差集的进入点)
+            if now && !inside {
+                rec.front_face = true;
+                enter = Some(rec);
+            } else if !now && inside {
+                rec.front_face = false;
+                intervals.push(Interval {
+                    enter: enter.take().unwrap(),
+                    exit: rec,
+                });
+            }
+            inside = now;
+        }
+        intervals
+    }
+}
+
+impl<A: Solid, B: Solid> Hittable for Csg<A, B> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // 先用包围盒排除, 避免为每条光线求出两侧的所有区间
+        if let Some(bbox) = self.bounding_box(r.time(), r.time()) {
+            if !bbox.hit(r, t_min, t_max) {
+                return None;
+            }
+        }
+        for i in self.intervals(r, t_min, t_max) {
+            for rec in [i.enter, i.exit].iter() {
+                if rec.t > t_max {
+                    return None;
+                }
+                if rec.t >= t_min {
+                    return Some(*rec);
+                }
+            }
+        }
+        None
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
+        let box_a = self.a.bounding_box(time0, time1)?;
+        match self.op {
+            CsgOp::Union => Some(surrounding_box(box_a, self.b.bounding_box(time0, time1)?)),
+            CsgOp::Intersection => {
+                let box_b = self.b.bounding_box(time0, time1)?;
+                let mut output = box_a;
+                for c in 0..3 {
+                    output.mini[c] = box_a.mini[c].max(box_b.mini[c]);
+                    output.maxi[c] = box_a.maxi[c].min(box_b.maxi[c]).max(output.mini[c]);
+                }
+                Some(output)
+            }
+            CsgOp::Difference => Some(box_a),
+        }
+    }
+
+    fn refit(&mut self, time0: f64, time1: f64) {
+        self.a.refit(time0, time1);
+        self.b.refit(time0, time1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::lambertian::Lambertian, object::sphere::Sphere, texture::solid_color::SolidColor,
+        Hit::Color,
+    };
+
+    type Ball = Sphere<Lambertian<SolidColor>>;
+
+    // 球心分别在 x = 0 与 x = 1 的两个单位球
+    fn csg(op: CsgOp) -> Csg<Ball, Ball> {
+        let mat = Lambertian::<SolidColor>::new(Color::new(0.5, 0.5, 0.5));
+        Csg::new(
+            Sphere::new(Point3::new(0., 0., 0.), 1., mat.clone()),
+            Sphere::new(Point3::new(1., 0., 0.), 1., mat),
+            op,
+        )
+    }
+    fn along_x(x: f64) -> Ray {
+        Ray::new(Point3::new(x, 0., 0.), Vec3::new(1., 0., 0.), 0.)
+    }
+    fn spans(intervals: &[Interval]) -> Vec<(f64, f64)> {
+        intervals.iter().map(|i| (i.enter.t, i.exit.t)).collect()
+    }
+    fn assert_spans(actual: &[(f64, f64)], expected: &[(f64, f64)]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!(
+                (a.0 - e.0).abs() < 1e-6 && (a.1 - e.1).abs() < 1e-6,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn merge_intervals() {
+        let r = along_x(-5.);
+        let (union, intersection, difference) = (
+            csg(CsgOp::Union),
+            csg(CsgOp::Intersection),
+            csg(CsgOp::Difference),
+        );
+        let union = union.intervals(&r, 0.001, INFINITY);
+        assert_spans(&spans(&union), &[(4., 7.)]);
+        let intersection = intersection.intervals(&r, 0.001, INFINITY);
+        assert_spans(&spans(&intersection), &[(5., 6.)]);
+        let difference = difference.intervals(&r, 0.001, INFINITY);
+        assert_spans(&spans(&difference), &[(4., 5.)]);
+
+        for i in union
+            .iter()
+            .chain(intersection.iter())
+            .chain(difference.iter())
+        {
+            assert!(i.enter.front_face && !i.exit.front_face);
+        }
+    }
+
+    #[test]
+    fn hit_respects_ray_range() {
+        let r = along_x(-5.);
+        let difference = csg(CsgOp::Difference);
+        // 差集的离开点在 b 的表面上, 法向与光线相反
+        let rec = difference.hit(&r, 4.5, INFINITY).unwrap();
+        assert!((rec.t - 5.).abs() < 1e-6);
+        assert!(!rec.front_face);
+        assert!(Vec3::dot(&rec.normal, &r.direction()) < 0.);
+        assert!(difference.hit(&r, 0.001, 3.).is_none());
+        assert!(difference.hit(&r, 5.5, INFINITY).is_none());
+    }
+
+    #[test]
+    fn ray_starting_inside() {
+        let r = along_x(0.5);
+        let union = csg(CsgOp::Union);
+        let intervals = union.intervals(&r, 0.001, INFINITY);
+        assert_eq!(intervals.len(), 1);
+        assert!(intervals[0].enter.t < 0.);
+        assert!((intervals[0].exit.t - 1.5).abs() < 1e-6);
+
+        let intersection = csg(CsgOp::Intersection);
+        let rec = intersection.hit(&r, 0.001, INFINITY).unwrap();
+        assert!((rec.t - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn miss() {
+        let r = Ray::new(Point3::new(-5., 3., 0.), Vec3::new(1., 0., 0.), 0.);
+        for &op in [CsgOp::Union, CsgOp::Intersection, CsgOp::Difference].iter() {
+            assert!(csg(op).intervals(&r, 0.001, INFINITY).is_empty());
+            assert!(csg(op).hit(&r, 0.001, INFINITY).is_none());
+        }
+    }
+}
@@ -2,8 +2,11 @@ use std::sync::Arc;
 
 use crate::{
     bvh::aabb::AABB,
-    object::rectangle::{Rectanglexy, Rectanglexz, Rectangleyz},
-    Hit::{Hittable, HittableList, Material, Point3},
+    object::{
+        csg::{surface_intervals, Interval, Solid},
+        rectangle,
+    },
+    Hit::{Hittable, HittableList, Material, Point3, Ray, Vec3},
 };
 
 pub struct Cube {
@@ -17,9 +20,10 @@ impl Cube {
     where
         T: 'static + Clone + Material,
     {
+        // 六个面的法向都朝外
         let mut side = HittableList::default();
 
-        side.objects.push(Arc::new(Rectanglexy::new(
+        side.objects.push(Arc::new(rectangle::xy(
             p0.x,
             p1.x,
             p0.y,
@@ -27,16 +31,11 @@ impl Cube {
             p1.z,
             ptr.clone(),
         )));
-        side.objects.push(Arc::new(Rectanglexy::new(
-            p0.x,
-            p1.x,
-            p0.y,
-            p1.y,
-            p0.z,
-            ptr.clone(),
-        )));
+        side.objects.push(Arc::new(
+            rectangle::xy(p0.x, p1.x, p0.y, p1.y, p0.z, ptr.clone()).flipped(),
+        ));
 
-        side.objects.push(Arc::new(Rectanglexz::new(
+        side.objects.push(Arc::new(rectangle::xz(
             p0.x,
             p1.x,
             p0.z,
@@ -44,16 +43,11 @@ impl Cube {
             p1.y,
             ptr.clone(),
         )));
-        side.objects.push(Arc::new(Rectanglexz::new(
-            p0.x,
-            p1.x,
-            p0.z,
-            p1.z,
-            p0.y,
-            ptr.clone(),
-        )));
+        side.objects.push(Arc::new(
+            rectangle::xz(p0.x, p1.x, p0.z, p1.z, p0.y, ptr.clone()).flipped(),
+        ));
 
-        side.objects.push(Arc::new(Rectangleyz::new(
+        side.objects.push(Arc::new(rectangle::yz(
             p0.y,
             p1.y,
             p0.z,
@@ -61,9 +55,9 @@ impl Cube {
             p1.x,
             ptr.clone(),
         )));
-        side.objects.push(Arc::new(Rectangleyz::new(
-            p0.y, p1.y, p0.z, p1.z, p0.x, ptr,
-        )));
+        side.objects.push(Arc::new(
+            rectangle::yz(p0.y, p1.y, p0.z, p1.z, p0.x, ptr).flipped(),
+        ));
 
         Self {
             box_min: p0,
@@ -77,8 +71,33 @@ impl Hittable for Cube {
     fn hit(&self, r: &crate::Hit::Ray, t_min: f64, t_max: f64) -> Option<crate::Hit::HitRecord> {
         self.side.hit(r, t_min, t_max)
     }
+    fn occluded(&self, r: &crate::Hit::Ray, t_min: f64, t_max: f64) -> bool {
+        self.side.occluded(r, t_min, t_max)
+    }
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<crate::bvh::aabb::AABB> {
         let output_box = AABB::new(self.box_min, self.box_max);
         Some(output_box)
     }
+    // 作为光源时均匀地选择一个面
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        self.side.pdf_value(o, v)
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        self.side.random(o)
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    fn power(&self) -> Option<f64> {
+        self.side.power()
+    }
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        self.side.sah_cost(time0, time1)
+    }
+}
+
+impl Solid for Cube {
+    fn intervals(&self, r: &Ray, t_min: f64, t_max: f64) -> Vec<Interval> {
+        surface_intervals(&self.side, r, t_min, t_max)
+    }
 }
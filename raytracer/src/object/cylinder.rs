@@ -0,0 +1,145 @@
+use std::f64::consts::PI;
+
+use crate::{
+    basic::random_double,
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    light::area_power,
+    Hit::{HitRecord, Hittable, Material, Point3, Ray, Vec3},
+};
+
+use super::quadric::{area_pdf_value, azimuth, solve_quadratic, Roots};
+
+const SIDE: usize = 0;
+const BOTTOM: usize = 1;
+const TOP: usize = 2;
+
+// 圆柱: 底面圆心 center, 沿 +y 方向高 height; 其他朝向用 Transformed 包装
+pub struct Cylinder<M: Material> {
+    pub center: Point3,
+    pub radius: f64,
+    pub height: f64,
+    pub capped: bool, // 是否有上下底面
+    pub mat: M,
+}
+
+impl<M: Material> Cylinder<M> {
+    pub fn new(center: Point3, radius: f64, height: f64, capped: bool, mat: M) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            capped,
+            mat,
+        }
+    }
+
+    fn roots(&self, r: &Ray) -> Roots {
+        let o = r.origin() - self.center;
+        let d = r.direction();
+        let mut roots = Roots::default();
+
+        let a = d.x() * d.x() + d.z() * d.z();
+        let half_b = o.x() * d.x() + o.z() * d.z();
+        let c = o.x() * o.x() + o.z() * o.z() - self.radius * self.radius;
+        if a > 0. {
+            for t in solve_quadratic(a, half_b, c) {
+                let y = o.y() + t * d.y();
+                if y >= 0. && y <= self.height {
+                    roots.push(t, SIDE);
+                }
+            }
+        }
+
+        if self.capped && d.y() != 0. {
+            for &(k, part) in [(0., BOTTOM), (self.height, TOP)].iter() {
+                let t = (k - o.y()) / d.y();
+                let x = o.x() + t * d.x();
+                let z = o.z() + t * d.z();
+                if x * x + z * z <= self.radius * self.radius {
+                    roots.push(t, part);
+                }
+            }
+        }
+        roots
+    }
+    // 局部坐标 p 处的外法向与uv
+    fn surface(&self, p: &Vec3, part: usize) -> (Vec3, f64, f64) {
+        let rho = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        match part {
+            SIDE => (
+                Vec3::new(p.x(), 0., p.z()) / self.radius,
+                azimuth(p),
+                p.y() / self.height,
+            ),
+            BOTTOM => (Vec3::new(0., -1., 0.), azimuth(p), rho / self.radius),
+            _ => (Vec3::new(0., 1., 0.), azimuth(p), rho / self.radius),
+        }
+    }
+    fn area(&self) -> f64 {
+        let side = 2. * PI * self.radius * self.height;
+        if self.capped {
+            side + 2. * PI * self.radius * self.radius
+        } else {
+            side
+        }
+    }
+}
+
+impl<M: Material> Hittable for Cylinder<M> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
+        let (t, part) = self.roots(r).nearest(t_min, t_max)?;
+        let p = r.at(t);
+        let (outward_normal, u, v) = self.surface(&(p - self.center), part);
+
+        let mut rec = HitRecord::new(t, p, Vec3::default(), bool::default(), &self.mat, u, v);
+        rec.set_face_normal(r, &outward_normal);
+        Some(rec)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        self.roots(r).any(t_min, t_max)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        Some(AABB::new(
+            self.center + Vec3::new(-self.radius, 0., -self.radius),
+            self.center + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        let r = Ray::new(*o, *v, 0.);
+        area_pdf_value(&self.roots(&r), &r, self.area(), |p, part| {
+            self.surface(&(*p - self.center), part).0
+        })
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        // 按面积选择侧面或底面, 再在其上均匀采样
+        let phi = 2. * PI * random_double();
+        let side = 2. * PI * self.radius * self.height;
+        let choose = random_double() * self.area();
+        let p = if choose < side {
+            Vec3::new(
+                self.radius * phi.cos(),
+                self.height * random_double(),
+                self.radius * phi.sin(),
+            )
+        } else {
+            let rho = self.radius * random_double().sqrt();
+            let y = if choose < side + PI * self.radius * self.radius {
+                0.
+            } else {
+                self.height
+            };
+            Vec3::new(rho * phi.cos(), y, rho * phi.sin())
+        };
+        self.center + p - *o
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    fn power(&self) -> Option<f64> {
+        area_power(&self.mat, &self.center, self.area())
+    }
+}
@@ -0,0 +1,121 @@
+// 非均匀介质的密度场
+use std::fs;
+
+use crate::{
+    bvh::aabb::AABB,
+    texture::perlin::Perlin,
+    Hit::{Point3, Vec3},
+};
+
+pub trait Density: Send + Sync {
+    fn density(&self, p: &Point3) -> f64;
+    // 密度的上界, delta/ratio tracking 以此为采样步长
+    fn majorant(&self) -> f64;
+}
+
+// 三维网格密度, 铺满 bbox, 网格点之间三线性插值
+pub struct GridDensity {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    data: Vec<f64>, // 下标 (k * ny + j) * nx + i
+    bbox: AABB,
+    scale: f64,
+    max: f64,
+}
+
+impl GridDensity {
+    pub fn new(nx: usize, ny: usize, nz: usize, data: Vec<f64>, bbox: AABB, scale: f64) -> Self {
+        assert_eq!(data.len(), nx * ny * nz, "density grid size mismatch");
+        let max = data.iter().cloned().fold(0., f64::max) * scale;
+        Self {
+            nx,
+            ny,
+            nz,
+            data,
+            bbox,
+            scale,
+            max,
+        }
+    }
+    // 文本格式: 开头为 nx ny nz, 其后 nx * ny * nz 个数, x 变化最快, z 变化最慢
+    pub fn from_file(filename: &str, bbox: AABB, scale: f64) -> Self {
+        let text = fs::read_to_string(filename).unwrap();
+        let mut numbers = text.split_whitespace();
+        let mut dim = || numbers.next().unwrap().parse::<usize>().unwrap();
+        let (nx, ny, nz) = (dim(), dim(), dim());
+        let data = numbers.map(|x| x.parse::<f64>().unwrap()).collect();
+        Self::new(nx, ny, nz, data, bbox, scale)
+    }
+
+    fn at(&self, i: usize, j: usize, k: usize) -> f64 {
+        self.data[(k * self.ny + j) * self.nx + i]
+    }
+}
+
+impl Density for GridDensity {
+    fn density(&self, p: &Point3) -> f64 {
+        let size = self.bbox.maxi - self.bbox.mini;
+        let dims = [self.nx, self.ny, self.nz];
+        let mut cell = [0; 3];
+        let mut frac = Vec3::default();
+        for a in 0..3 {
+            let x = (p[a] - self.bbox.mini[a]) / size[a];
+            if !(0. ..=1.).contains(&x) {
+                return 0.;
+            }
+            // 网格点均匀分布在包围盒上(含边界), 某方向只有一个点时该方向上为常数
+            let c = a as usize;
+            let g = x * (dims[c] - 1) as f64;
+            cell[c] = (g as usize).min(dims[c].saturating_sub(2));
+            frac[a] = (g - cell[c] as f64).min(1.);
+        }
+
+        let mut accum = 0.;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let i = (cell[0] + di).min(self.nx - 1);
+                    let j = (cell[1] + dj).min(self.ny - 1);
+                    let k = (cell[2] + dk).min(self.nz - 1);
+                    let w = (if di == 1 { frac.x } else { 1. - frac.x })
+                        * (if dj == 1 { frac.y } else { 1. - frac.y })
+                        * (if dk == 1 { frac.z } else { 1. - frac.z });
+                    accum += w * self.at(i, j, k);
+                }
+            }
+        }
+        accum * self.scale
+    }
+    fn majorant(&self) -> f64 {
+        self.max
+    }
+}
+
+// Perlin 湍流密度: density * min(turb(p * scale), 1), 上界即为 density
+pub struct NoiseDensity {
+    noise: Perlin,
+    density: f64,
+    scale: f64,
+    depth: isize,
+}
+
+impl NoiseDensity {
+    pub fn new(density: f64, scale: f64, depth: isize) -> Self {
+        Self {
+            noise: Perlin::new(),
+            density,
+            scale,
+            depth,
+        }
+    }
+}
+
+impl Density for NoiseDensity {
+    fn density(&self, p: &Point3) -> f64 {
+        self.density * self.noise.turb(&(*p * self.scale), self.depth).min(1.)
+    }
+    fn majorant(&self) -> f64 {
+        self.density
+    }
+}
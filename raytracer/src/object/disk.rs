@@ -0,0 +1,115 @@
+use std::f64::consts::PI;
+
+use crate::{
+    basic::random_double,
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    light::area_power,
+    material::ONB,
+    Hit::{HitRecord, Hittable, Material, Point3, Ray, Vec3},
+};
+
+// 圆盘: 圆心 center, 法向 normal, 半径 radius
+pub struct Disk<M: Material> {
+    center: Point3,
+    radius: f64,
+    uvw: ONB, // w 为法向, u/v 为盘面上的两个方向
+    mat: M,
+}
+
+impl<M: Material> Disk<M> {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, mat: M) -> Self {
+        Self {
+            center,
+            radius,
+            uvw: ONB::build(&normal),
+            mat,
+        }
+    }
+
+    // 返回交点的 t 及交点相对圆心的偏移
+    fn intersect(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, Vec3)> {
+        let normal = self.uvw.w();
+        let denom = Vec3::dot(&normal, &r.direction());
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+        let t = Vec3::dot(&normal, &(self.center - r.origin())) / denom;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let offset = r.at(t) - self.center;
+        if offset.len_square() > self.radius * self.radius {
+            return None;
+        }
+        Some((t, offset))
+    }
+}
+
+impl<M: Material> Hittable for Disk<M> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
+        let (t, offset) = self.intersect(r, t_min, t_max)?;
+
+        // 极坐标作为uv: u 为角度, v 为到圆心的距离
+        let phi = Vec3::dot(&offset, &self.uvw.v()).atan2(Vec3::dot(&offset, &self.uvw.u()));
+        let u = (phi + PI) / (2. * PI);
+        let v = offset.len() / self.radius;
+
+        let mut rec = HitRecord::new(
+            t,
+            r.at(t),
+            Vec3::default(),
+            bool::default(),
+            &self.mat,
+            u,
+            v,
+        );
+        rec.set_face_normal(r, &self.uvw.w());
+        Some(rec)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        self.intersect(r, t_min, t_max).is_some()
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        // 圆盘在第 c 轴上的半宽为 radius * sqrt(1 - n_c^2)
+        let n = self.uvw.w();
+        let mut half = Vec3::default();
+        for c in 0..3 {
+            half[c] = self.radius * (1. - n[c] * n[c]).max(0.).sqrt() + 0.0001;
+        }
+        Some(AABB::new(self.center - half, self.center + half))
+    }
+
+    fn pdf_value(&self, origin: &Point3, v: &Vec3) -> f64 {
+        let getn = self.intersect(&Ray::new(*origin, *v, 0.), 0.001, f64::INFINITY);
+        if getn.is_none() {
+            return 0.;
+        }
+        let t = getn.unwrap().0;
+        let area = PI * self.radius * self.radius;
+        let distance_squared = t.powi(2) * v.len_square();
+        let cos = Vec3::dot(v, &self.uvw.w()).abs() / v.len();
+        // 掠射时密度趋于无穷, 直接视为采样不到
+        if cos < 1e-8 {
+            return 0.;
+        }
+
+        distance_squared / (cos * area)
+    }
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        // 面积均匀采样
+        let r = self.radius * random_double().sqrt();
+        let phi = 2. * PI * random_double();
+        let point = self.center + r * phi.cos() * self.uvw.u() + r * phi.sin() * self.uvw.v();
+        point - *origin
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    fn power(&self) -> Option<f64> {
+        area_power(&self.mat, &self.center, PI * self.radius * self.radius)
+    }
+}
@@ -0,0 +1,249 @@
+#![allow(clippy::many_single_char_names)]
+use image::GenericImageView;
+
+use crate::{
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    texture::perlin::Perlin,
+    Hit::{HitRecord, Hittable, Material, Point3, Ray, Vec3},
+};
+
+// 高度场地形: nx * nz 个网格点的高度, 每个格子由两个三角形组成
+// 覆盖 origin 到 origin + size 的范围, 高度范围 [origin.y, origin.y + size.y]
+pub struct Heightfield<M: Material> {
+    nx: usize,
+    nz: usize,
+    heights: Vec<f64>,  // 世界坐标下的 y, 下标 k * nx + i
+    normals: Vec<Vec3>, // 网格点的法向, 用于插值
+    origin: Point3,
+    size: Vec3,
+    bbox: AABB,
+    pub mat: M,
+}
+
+impl<M: Material> Heightfield<M> {
+    // 由 [0, 1] 的高度构造, data[k * nx + i] 对应 x 方向第 i 个, z 方向第 k 个网格点
+    pub fn new(nx: usize, nz: usize, data: &[f64], origin: Point3, size: Vec3, mat: M) -> Self {
+        assert!(nx >= 2 && nz >= 2, "heightfield needs at least 2x2 samples");
+        let heights: Vec<f64> = data.iter().map(|h| origin.y + h * size.y).collect();
+
+        let dx = size.x / (nx - 1) as f64;
+        let dz = size.z / (nz - 1) as f64;
+        let mut normals = Vec::with_capacity(nx * nz);
+        for k in 0..nz {
+            for i in 0..nx {
+                // 中心差分, 边界处用单侧差分
+                let (i0, i1) = (i.saturating_sub(1), (i + 1).min(nx - 1));
+                let (k0, k1) = (k.saturating_sub(1), (k + 1).min(nz - 1));
+                let slope_x =
+                    (heights[k * nx + i1] - heights[k * nx + i0]) / ((i1 - i0) as f64 * dx);
+                let slope_z =
+                    (heights[k1 * nx + i] - heights[k0 * nx + i]) / ((k1 - k0) as f64 * dz);
+                normals.push(Vec3::new(-slope_x, 1., -slope_z).unit_vector());
+            }
+        }
+
+        let lowest = heights.iter().cloned().fold(f64::INFINITY, f64::min);
+        let highest = heights.iter().cloned().fold(-f64::INFINITY, f64::max);
+        let bbox = AABB::new(
+            Point3::new(origin.x, lowest - 0.0001, origin.z),
+            Point3::new(origin.x + size.x, highest + 0.0001, origin.z + size.z),
+        );
+
+        Self {
+            nx,
+            nz,
+            heights,
+            normals,
+            origin,
+            size,
+            bbox,
+            mat,
+        }
+    }
+    // 灰度图: 列对应 x, 行对应 z, 与 ImageTexture 的 uv 约定一致
+    pub fn from_image(filename: &str, origin: Point3, size: Vec3, mat: M) -> Self {
+        let img = image::open(filename).unwrap();
+        let (width, height) = (img.width() as usize, img.height() as usize);
+        let luma = img.to_luma8();
+        let data: Vec<f64> = luma.pixels().map(|p| p[0] as f64 / 255.).collect();
+        Self::new(width, height, &data, origin, size, mat)
+    }
+    // Perlin 湍流, scale 为噪声频率, 高度归一化到 [0, 1]
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_perlin(
+        noise: &Perlin,
+        depth: isize,
+        scale: f64,
+        nx: usize,
+        nz: usize,
+        origin: Point3,
+        size: Vec3,
+        mat: M,
+    ) -> Self {
+        let mut data = Vec::with_capacity(nx * nz);
+        for k in 0..nz {
+            for i in 0..nx {
+                let p = Point3::new(
+                    i as f64 / (nx - 1) as f64 * size.x,
+                    0.,
+                    k as f64 / (nz - 1) as f64 * size.z,
+                );
+                data.push(noise.turb(&(p * scale), depth));
+            }
+        }
+        let highest = data.iter().cloned().fold(0., f64::max);
+        if highest > 0. {
+            data.iter_mut().for_each(|h| *h /= highest);
+        }
+        Self::new(nx, nz, &data, origin, size, mat)
+    }
+
+    fn vertex(&self, i: usize, k: usize) -> Point3 {
+        Point3::new(
+            self.origin.x + i as f64 / (self.nx - 1) as f64 * self.size.x,
+            self.heights[k * self.nx + i],
+            self.origin.z + k as f64 / (self.nz - 1) as f64 * self.size.z,
+        )
+    }
+
+    // 返回 t, 三角形三个顶点的下标和重心坐标
+    fn march(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, [usize; 3], f64, f64)> {
+        let (t_enter, t_exit) = self.bbox.clip(r, t_min, t_max)?;
+        let o = r.origin();
+        let d = r.direction();
+        let dx = self.size.x / (self.nx - 1) as f64;
+        let dz = self.size.z / (self.nz - 1) as f64;
+
+        // 二维DDA: 沿 x, z 方向依次穿过格子
+        let p = r.at(t_enter);
+        let cell = |v: f64, n: usize| (v.floor().max(0.) as usize).min(n - 2);
+        let mut i = cell((p.x - self.origin.x) / dx, self.nx);
+        let mut k = cell((p.z - self.origin.z) / dz, self.nz);
+        let step_i: isize = if d.x > 0. { 1 } else { -1 };
+        let step_k: isize = if d.z > 0. { 1 } else { -1 };
+        let delta_x = (dx / d.x).abs();
+        let delta_z = (dz / d.z).abs();
+        let boundary = |c: usize, step: isize, lo: f64, delta: f64, ov: f64, dv: f64| {
+            if dv == 0. {
+                return f64::INFINITY;
+            }
+            let next = if step > 0 { c + 1 } else { c };
+            (lo + next as f64 * delta - ov) / dv
+        };
+        let mut next_x = boundary(i, step_i, self.origin.x, dx, o.x, d.x);
+        let mut next_z = boundary(k, step_k, self.origin.z, dz, o.z, d.z);
+
+        let mut t0 = t_enter;
+        loop {
+            let t1 = next_x.min(next_z).min(t_exit);
+
+            // 光线在该格子内的高度范围与地形不重叠时跳过
+            let corners = [
+                k * self.nx + i,
+                k * self.nx + i + 1,
+                (k + 1) * self.nx + i,
+                (k + 1) * self.nx + i + 1,
+            ];
+            let lowest = corners
+                .iter()
+                .map(|&c| self.heights[c])
+                .fold(f64::INFINITY, f64::min);
+            let highest = corners
+                .iter()
+                .map(|&c| self.heights[c])
+                .fold(-f64::INFINITY, f64::max);
+            let (y0, y1) = (o.y + t0 * d.y, o.y + t1 * d.y);
+            if y0.min(y1) <= highest && y0.max(y1) >= lowest {
+                let mut best: Option<(f64, [usize; 3], f64, f64)> = None;
+                let tris = [
+                    [corners[0], corners[3], corners[1]],
+                    [corners[0], corners[2], corners[3]],
+                ];
+                for tri in tris.iter() {
+                    let v0 = self.vertex(tri[0] % self.nx, tri[0] / self.nx);
+                    let v1 = self.vertex(tri[1] % self.nx, tri[1] / self.nx);
+                    let v2 = self.vertex(tri[2] % self.nx, tri[2] / self.nx);
+                    if let Some((t, b1, b2)) = intersect_triangle(r, v0, v1, v2) {
+                        let closer = match best {
+                            Some(b) => t < b.0,
+                            None => true,
+                        };
+                        if t >= t_min && t <= t_max && closer {
+                            best = Some((t, *tri, b1, b2));
+                        }
+                    }
+                }
+                if best.is_some() {
+                    return best;
+                }
+            }
+
+            if t1 >= t_exit {
+                return None;
+            }
+            if next_x < next_z {
+                if (step_i < 0 && i == 0) || (step_i > 0 && i + 2 >= self.nx) {
+                    return None;
+                }
+                i = (i as isize + step_i) as usize;
+                next_x += delta_x;
+            } else {
+                if (step_k < 0 && k == 0) || (step_k > 0 && k + 2 >= self.nz) {
+                    return None;
+                }
+                k = (k as isize + step_k) as usize;
+                next_z += delta_z;
+            }
+            t0 = t1;
+        }
+    }
+}
+
+// Moller-Trumbore, 返回 t 与 v1, v2 的重心坐标
+fn intersect_triangle(r: &Ray, v0: Point3, v1: Point3, v2: Point3) -> Option<(f64, f64, f64)> {
+    let e1 = v1 - v0;
+    let e2 = v2 - v0;
+    let pvec = r.direction().cross(e2);
+    let det = Vec3::dot(&e1, &pvec);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1. / det;
+    let tvec = r.origin() - v0;
+    let b1 = Vec3::dot(&tvec, &pvec) * inv_det;
+    if !(0. ..=1.).contains(&b1) {
+        return None;
+    }
+    let qvec = tvec.cross(e1);
+    let b2 = Vec3::dot(&r.direction(), &qvec) * inv_det;
+    if b2 < 0. || b1 + b2 > 1. {
+        return None;
+    }
+    Some((Vec3::dot(&e2, &qvec) * inv_det, b1, b2))
+}
+
+impl<M: Material> Hittable for Heightfield<M> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
+        let (t, tri, b1, b2) = self.march(r, t_min, t_max)?;
+        let p = r.at(t);
+        let normal = ((1. - b1 - b2) * self.normals[tri[0]]
+            + b1 * self.normals[tri[1]]
+            + b2 * self.normals[tri[2]])
+            .unit_vector();
+        let u = (p.x - self.origin.x) / self.size.x;
+        let v = 1. - (p.z - self.origin.z) / self.size.z;
+
+        let mut rec = HitRecord::new(t, p, Vec3::default(), bool::default(), &self.mat, u, v);
+        rec.set_face_normal(r, &normal);
+        Some(rec)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        self.march(r, t_min, t_max).is_some()
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        Some(self.bbox)
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    bvh::{aabb::AABB, stats::BvhStats},
+    material::isotropic::Isotropic,
+    object::{density::Density, medium::boundary_intervals},
+    texture::solid_color::SolidColor,
+    Hit::{random_double, Color, HitRecord, Hittable, Material, Ray, Vec3},
+};
+
+// 密度随位置变化的介质, 以 majorant 为上界做 delta tracking / ratio tracking
+pub struct HeterogeneousMedium<H: Hittable, D: Density, M: Material> {
+    boundary: H,
+    density: D,
+    phase: M,
+}
+
+impl<H: Hittable, D: Density> HeterogeneousMedium<H, D, Isotropic<SolidColor>> {
+    pub fn new(boundary: H, density: D, color: Color) -> Self {
+        Self {
+            boundary,
+            density,
+            phase: Isotropic::<SolidColor>::new_color(color),
+        }
+    }
+}
+
+impl<H: Hittable, D: Density, M: Material> HeterogeneousMedium<H, D, M> {
+    pub fn new_phase(boundary: H, density: D, phase: M) -> Self {
+        Self {
+            boundary,
+            density,
+            phase,
+        }
+    }
+
+    // delta tracking: 按 majorant 采样候选碰撞点, 以 density / majorant 的概率接受
+    fn sample_t(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<f64> {
+        let majorant = self.density.majorant();
+        if majorant <= 0. {
+            return None;
+        }
+        let ray_length = r.direction().len();
+
+        // 指数分布无记忆, 每段边界内可以重新开始采样
+        for (t0, t1) in boundary_intervals(&self.boundary, r, t_min, t_max) {
+            let mut t = t0;
+            loop {
+                t -= (1. - random_double()).ln() / (majorant * ray_length);
+                if t >= t1 {
+                    break;
+                }
+                if random_double() * majorant < self.density.density(&r.at(t)) {
+                    return Some(t);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<H: Hittable, D: Density, M: Material> Hittable for HeterogeneousMedium<H, D, M> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = self.sample_t(r, t_min, t_max)?;
+        Some(HitRecord::new(
+            t,
+            r.at(t),
+            Vec3::new(1., 0., 0.),
+            true,
+            &self.phase,
+            0.,
+            0.,
+        ))
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.sample_t(r, t_min, t_max).is_some()
+    }
+    // ratio tracking: 每个候选点处透射率乘上 1 - density / majorant
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let majorant = self.density.majorant();
+        if majorant <= 0. {
+            return 1.;
+        }
+        let ray_length = r.direction().len();
+
+        let mut transmittance = 1.;
+        for (t0, t1) in boundary_intervals(&self.boundary, r, t_min, t_max) {
+            let mut t = t0;
+            loop {
+                t -= (1. - random_double()).ln() / (majorant * ray_length);
+                if t >= t1 {
+                    break;
+                }
+                transmittance *= 1. - self.density.density(&r.at(t)) / majorant;
+                if transmittance <= 0. {
+                    return 0.;
+                }
+            }
+        }
+        transmittance
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
+        self.boundary.bounding_box(time0, time1)
+    }
+    fn refit(&mut self, time0: f64, time1: f64) {
+        self.boundary.refit(time0, time1);
+    }
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        self.boundary.sah_cost(time0, time1)
+    }
+    fn bvh_stats(&self, depth: usize, stats: &mut BvhStats) -> bool {
+        stats.memory += std::mem::size_of::<Self>() - std::mem::size_of::<H>();
+        self.boundary.bvh_stats(depth, stats)
+    }
+}
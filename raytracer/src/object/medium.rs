@@ -1,8 +1,8 @@
 #![allow(clippy::question_mark)]
-use std::f64::INFINITY;
-
 use crate::{
+    bvh::stats::BvhStats,
     material::isotropic::Isotropic,
+    object::csg::surface_intervals,
     texture::{solid_color::SolidColor, Texture},
     Hit::{random_double, Color, HitRecord, Hittable, Material, Vec3},
 };
@@ -35,55 +35,67 @@ impl<H: Hittable, T: Texture> ConstantMedium<H, Isotropic<T>> {
     }
 }
 
-impl<H: Hittable, M: Material> Hittable for ConstantMedium<H, M> {
-    fn bounding_box(&self, time0: f64, time1: f64) -> Option<crate::bvh::aabb::AABB> {
-        self.boundary.bounding_box(time0, time1)
-    }
-
-    fn hit(&self, r: &crate::Hit::Ray, t_min: f64, t_max: f64) -> Option<crate::Hit::HitRecord> {
-        let enableDebug = false;
-        let debugging = enableDebug && random_double() < 0.00001;
-
-        let rec1 = self.boundary.hit(r, -INFINITY, INFINITY);
-        if rec1.is_none() {
-            return None;
-        }
-        let mut rec1 = rec1.unwrap();
-
-        let rec2 = self.boundary.hit(r, rec1.t + 0.0001, INFINITY);
-        if rec2.is_none() {
-            return None;
-        }
-        let mut rec2 = rec2.unwrap();
-
-        if debugging {
-            println!("t_min = {} t_max = {}", rec1.t, rec2.t);
-        }
-
-        if rec1.t < t_min {
-            rec1.t = t_min;
-        }
-        if rec2.t > t_max {
-            rec2.t = t_max;
+impl<H: Hittable, M: Material> ConstantMedium<H, M> {
+    // 任意相函数材质, 如 Volume::with_phase(texture, HenyeyGreenstein::new(0.8))
+    pub fn new_phase(boundary: H, density: f64, phase: M) -> Self {
+        ConstantMedium {
+            boundary,
+            phase,
+            neg_inv_density: -1. / density,
         }
+    }
+    // 按指数分布采样光线在介质中发生散射的位置, 距离在边界内的各段上累计
+    fn sample_t(&self, r: &crate::Hit::Ray, t_min: f64, t_max: f64) -> Option<f64> {
+        let ray_length = r.direction().len();
+        let mut hit_distance = self.neg_inv_density * (random_double().ln());
 
-        if rec1.t >= rec2.t {
-            return None;
+        for (t0, t1) in boundary_intervals(&self.boundary, r, t_min, t_max) {
+            let distance_inside_boundary = (t1 - t0) * ray_length;
+            if hit_distance <= distance_inside_boundary {
+                return Some(t0 + hit_distance / ray_length);
+            }
+            hit_distance -= distance_inside_boundary;
         }
+        None
+    }
+}
 
-        if rec1.t < 0. {
-            rec1.t = 0.;
-        }
+// 光线在边界内的各段参数区间, 已截断到 [t_min, t_max]
+// 边界需要是封闭曲面且法向朝外, 可以非凸; 光线起点在边界内(如相机在雾中)时第一段从 t_min 开始
+pub fn boundary_intervals<H: Hittable + ?Sized>(
+    boundary: &H,
+    r: &crate::Hit::Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Vec<(f64, f64)> {
+    surface_intervals(boundary, r, t_min, t_max)
+        .iter()
+        .map(|i| (i.enter.t.max(t_min), i.exit.t.min(t_max)))
+        .filter(|(t0, t1)| t0 < t1)
+        .collect()
+}
 
-        let ray_length = r.direction().len();
-        let distance_inside_boundary = (rec2.t - rec1.t) * ray_length;
-        let hit_distance = self.neg_inv_density * (random_double().ln());
+impl<H: Hittable, M: Material> Hittable for ConstantMedium<H, M> {
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<crate::bvh::aabb::AABB> {
+        self.boundary.bounding_box(time0, time1)
+    }
+    fn refit(&mut self, time0: f64, time1: f64) {
+        self.boundary.refit(time0, time1);
+    }
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        self.boundary.sah_cost(time0, time1)
+    }
+    fn bvh_stats(&self, depth: usize, stats: &mut BvhStats) -> bool {
+        stats.memory += std::mem::size_of::<Self>() - std::mem::size_of::<H>();
+        self.boundary.bvh_stats(depth, stats)
+    }
 
-        if hit_distance > distance_inside_boundary {
+    fn hit(&self, r: &crate::Hit::Ray, t_min: f64, t_max: f64) -> Option<crate::Hit::HitRecord> {
+        let t = self.sample_t(r, t_min, t_max);
+        if t.is_none() {
             return None;
         }
-
-        let t = rec1.t + hit_distance / ray_length;
+        let t = t.unwrap();
         let p = r.at(t);
 
         Some(HitRecord::new(
@@ -96,4 +108,14 @@ impl<H: Hittable, M: Material> Hittable for ConstantMedium<H, M> {
             0.,
         ))
     }
+    fn occluded(&self, r: &crate::Hit::Ray, t_min: f64, t_max: f64) -> bool {
+        self.sample_t(r, t_min, t_max).is_some()
+    }
+    fn transmittance(&self, r: &crate::Hit::Ray, t_min: f64, t_max: f64) -> f64 {
+        let distance: f64 = boundary_intervals(&self.boundary, r, t_min, t_max)
+            .iter()
+            .map(|(t0, t1)| (t1 - t0) * r.direction().len())
+            .sum();
+        (distance / self.neg_inv_density).exp()
+    }
 }
@@ -1,8 +1,23 @@
+pub mod animated;
+pub mod capsule;
+pub mod cone;
+pub mod csg;
 pub mod cube;
+pub mod cylinder;
+pub mod density;
+pub mod disk;
+pub mod heightfield;
+pub mod heterogeneous;
 pub mod medium;
 pub mod move_sphere;
+pub mod paraboloid;
+pub mod quad;
+pub mod quadric;
 pub mod rectangle;
 pub mod rotate;
+pub mod sdf;
 pub mod sphere;
+pub mod torus;
+pub mod transformed;
 pub mod translate;
 pub mod triangle;
@@ -1,10 +1,18 @@
+use std::{f64::consts::PI, f64::INFINITY};
+
 use super::super::Hit::{HitRecord, Hittable};
 pub use crate::basic::{
     RAY::Ray,
     VEC3::{Point3, Vec3},
 };
 use crate::{
-    bvh::aabb::{surrounding_box, AABB},
+    bvh::{
+        aabb::{surrounding_box, AABB},
+        stats::count_primitive_test,
+    },
+    light::area_power,
+    material::ONB,
+    pdf::random_to_sphere,
     Hit::Material,
 };
 
@@ -44,6 +52,7 @@ impl<M: Material> MoveSphere<M> {
 
 impl<M: Material> Hittable for MoveSphere<M> {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
         let oc = r.origin() - self.center(r.time());
         let a = r.direction().len_square();
         let half_b = Vec3::dot(&oc, &r.direction());
@@ -79,6 +88,25 @@ impl<M: Material> Hittable for MoveSphere<M> {
         Some(rec)
     }
 
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        let oc = r.origin() - self.center(r.time());
+        let a = r.direction().len_square();
+        let half_b = Vec3::dot(&oc, &r.direction());
+        let c = oc.len_square() - self.radius * self.radius;
+
+        let discrim = half_b.powi(2) - a * c;
+        if discrim < 0. {
+            return false;
+        }
+
+        // 两个根中有一个在范围内即可, 不需要计算uv和法向
+        let sqrtd = discrim.sqrt();
+        let root0 = (-half_b - sqrtd) / a;
+        let root1 = (-half_b + sqrtd) / a;
+        (root0 >= t_min && root0 <= t_max) || (root1 >= t_min && root1 <= t_max)
+    }
+
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
         let cub = Vec3::new(self.radius, self.radius, self.radius);
         let box0 = AABB::new(self.center(time0) - cub, self.center(time0) + cub);
@@ -86,4 +114,30 @@ impl<M: Material> Hittable for MoveSphere<M> {
 
         Some(surrounding_box(box0, box1))
     }
+
+    // 光源采样与 pdf_value 中的光线时间都取 0, 球心也取 0 时刻的位置
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        if self.hit(&Ray::new(*o, *v, 0.), 0.001, INFINITY).is_none() {
+            return 0.;
+        }
+
+        let distance_squared = (self.center(0.) - *o).len_square();
+        let cos_theta_max = (1. - self.radius.powi(2) / distance_squared).sqrt();
+        let solid_angle = 2. * PI * (1. - cos_theta_max);
+
+        1. / solid_angle
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        let direction = self.center(0.) - *o;
+        let distance_squared = direction.len_square();
+        let uvw = ONB::build(&direction);
+        uvw.local(random_to_sphere(self.radius, distance_squared))
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    fn power(&self) -> Option<f64> {
+        let center = self.center(0.);
+        area_power(&self.mat, &center, 4. * PI * self.radius * self.radius)
+    }
 }
@@ -0,0 +1,109 @@
+use std::f64::consts::PI;
+
+use crate::{
+    basic::random_double,
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    light::area_power,
+    Hit::{HitRecord, Hittable, Material, Point3, Ray, Vec3},
+};
+
+use super::quadric::{area_pdf_value, azimuth, solve_quadratic, Roots};
+
+// 旋转抛物面(开口向上, 无盖): 顶点 center, y = height * (x^2 + z^2) / radius^2, 0 <= y <= height
+pub struct Paraboloid<M: Material> {
+    pub center: Point3,
+    pub radius: f64,
+    pub height: f64,
+    pub mat: M,
+}
+
+impl<M: Material> Paraboloid<M> {
+    pub fn new(center: Point3, radius: f64, height: f64, mat: M) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            mat,
+        }
+    }
+
+    fn roots(&self, r: &Ray) -> Roots {
+        let o = r.origin() - self.center;
+        let d = r.direction();
+        let mut roots = Roots::default();
+
+        // x^2 + z^2 = k y
+        let k = self.radius * self.radius / self.height;
+        let a = d.x() * d.x() + d.z() * d.z();
+        let half_b = o.x() * d.x() + o.z() * d.z() - k * d.y() / 2.;
+        let c = o.x() * o.x() + o.z() * o.z() - k * o.y();
+        for t in solve_quadratic(a, half_b, c) {
+            let y = o.y() + t * d.y();
+            if y <= self.height {
+                roots.push(t, 0);
+            }
+        }
+        roots
+    }
+    fn surface(&self, p: &Vec3) -> (Vec3, f64, f64) {
+        let k = self.radius * self.radius / self.height;
+        let outward_normal = Vec3::new(2. * p.x(), -k, 2. * p.z()).unit_vector();
+        (outward_normal, azimuth(p), p.y() / self.height)
+    }
+    // s = 4 h^2 / r^4, 面元 2 pi rho sqrt(1 + s rho^2) d rho
+    fn area(&self) -> f64 {
+        let r = self.radius;
+        let h = self.height;
+        PI * r / (6. * h * h) * ((r * r + 4. * h * h).powf(1.5) - r.powi(3))
+    }
+}
+
+impl<M: Material> Hittable for Paraboloid<M> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
+        let (t, _) = self.roots(r).nearest(t_min, t_max)?;
+        let p = r.at(t);
+        let (outward_normal, u, v) = self.surface(&(p - self.center));
+
+        let mut rec = HitRecord::new(t, p, Vec3::default(), bool::default(), &self.mat, u, v);
+        rec.set_face_normal(r, &outward_normal);
+        Some(rec)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        self.roots(r).any(t_min, t_max)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        Some(AABB::new(
+            self.center + Vec3::new(-self.radius, 0., -self.radius),
+            self.center + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        let r = Ray::new(*o, *v, 0.);
+        area_pdf_value(&self.roots(&r), &r, self.area(), |p, _| {
+            self.surface(&(*p - self.center)).0
+        })
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        // 反解 rho 的分布函数 ((1 + s rho^2)^1.5 - 1) / ((1 + s r^2)^1.5 - 1)
+        let s = 4. * self.height.powi(2) / self.radius.powi(4);
+        let total = (1. + s * self.radius.powi(2)).powf(1.5) - 1.;
+        let rho = (((1. + random_double() * total).powf(2. / 3.) - 1.) / s).sqrt();
+        let phi = 2. * PI * random_double();
+        let p = Vec3::new(
+            rho * phi.cos(),
+            self.height * (rho / self.radius).powi(2),
+            rho * phi.sin(),
+        );
+        self.center + p - *o
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    fn power(&self) -> Option<f64> {
+        area_power(&self.mat, &self.center, self.area())
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    basic::random_double,
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    light::area_power,
+    Hit::{HitRecord, Hittable, Material, Point3, Ray, Vec3},
+};
+
+// 平行四边形: 由一个顶点 q 与两条边 u, v 张成, 即 q + a * u + b * v (a, b ∈ [0, 1])
+pub struct Quad<M: Material> {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,      // n / (n · n), 用于求平面上的坐标 (a, b)
+    normal: Vec3, // 单位法向, 默认 u × v 方向
+    d: f64,       // 平面方程 normal · p = d
+    area: f64,
+    mat: M,
+}
+
+impl<M: Material> Quad<M> {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, mat: M) -> Self {
+        let n = u.cross(v);
+        let normal = n.unit_vector();
+        Self {
+            q,
+            u,
+            v,
+            w: n / n.len_square(),
+            normal,
+            d: Vec3::dot(&normal, &q),
+            area: n.len(),
+            mat,
+        }
+    }
+    // 法向取反(外侧变为 v × u 方向), uv 不变
+    pub fn flipped(mut self) -> Self {
+        self.normal = -self.normal;
+        self.d = -self.d;
+        self
+    }
+
+    // 返回交点的 t 与平面坐标 (a, b)
+    fn intersect(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64, f64)> {
+        let denom = Vec3::dot(&self.normal, &r.direction());
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+        let t = (self.d - Vec3::dot(&self.normal, &r.origin())) / denom;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let planar = r.at(t) - self.q;
+        let a = Vec3::dot(&self.w, &planar.cross(self.v));
+        let b = Vec3::dot(&self.w, &self.u.cross(planar));
+        if !(0. ..=1.).contains(&a) || !(0. ..=1.).contains(&b) {
+            return None;
+        }
+        Some((t, a, b))
+    }
+}
+
+impl<M: Material> Hittable for Quad<M> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
+        let (t, a, b) = self.intersect(r, t_min, t_max)?;
+
+        let mut rec = HitRecord::new(
+            t,
+            r.at(t),
+            Vec3::default(),
+            bool::default(),
+            &self.mat,
+            a,
+            b,
+        );
+        rec.set_face_normal(r, &self.normal);
+        Some(rec)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        self.intersect(r, t_min, t_max).is_some()
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        let corners = [self.q + self.u, self.q + self.v, self.q + self.u + self.v];
+        let mut mini = self.q;
+        let mut maxi = self.q;
+        for p in corners.iter() {
+            for c in 0..3 {
+                mini[c] = mini[c].min(p[c]);
+                maxi[c] = maxi[c].max(p[c]);
+            }
+        }
+        // 与坐标轴平行时包围盒厚度为0, 稍微撑开
+        for c in 0..3 {
+            if maxi[c] - mini[c] < 0.0001 {
+                mini[c] -= 0.0001;
+                maxi[c] += 0.0001;
+            }
+        }
+        Some(AABB::new(mini, maxi))
+    }
+
+    fn pdf_value(&self, origin: &Point3, v: &Vec3) -> f64 {
+        let getn = self.intersect(&Ray::new(*origin, *v, 0.), 0.001, f64::INFINITY);
+        if getn.is_none() {
+            return 0.;
+        }
+        let t = getn.unwrap().0;
+        let distance_squared = t.powi(2) * v.len_square();
+        let cos = Vec3::dot(v, &self.normal).abs() / v.len();
+        // 掠射时密度趋于无穷, 直接视为采样不到
+        if cos < 1e-8 {
+            return 0.;
+        }
+
+        distance_squared / (cos * self.area)
+    }
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        let point = self.q + random_double() * self.u + random_double() * self.v;
+        point - *origin
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    fn power(&self) -> Option<f64> {
+        area_power(
+            &self.mat,
+            &(self.q + 0.5 * self.u + 0.5 * self.v),
+            self.area,
+        )
+    }
+}
@@ -0,0 +1,198 @@
+// 二次(及四次)曲面的公共部分: 交点集合, 方程求根, 面光源的pdf
+use std::f64::consts::PI;
+
+use crate::Hit::{Point3, Ray, Vec3};
+
+// 光线与曲面所有交点的 t 以及所在的部分(侧面/底面...), 未排序, 最多4个
+#[derive(Default)]
+pub struct Roots {
+    t: [(f64, usize); 4],
+    len: usize,
+}
+
+impl Roots {
+    pub fn push(&mut self, t: f64, part: usize) {
+        if self.len < 4 {
+            self.t[self.len] = (t, part);
+            self.len += 1;
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &(f64, usize)> {
+        self.t[..self.len].iter()
+    }
+    pub fn nearest(&self, t_min: f64, t_max: f64) -> Option<(f64, usize)> {
+        self.iter()
+            .filter(|(t, _)| *t >= t_min && *t <= t_max)
+            .fold(None, |acc: Option<(f64, usize)>, &cur| match acc {
+                Some(best) if best.0 <= cur.0 => Some(best),
+                _ => Some(cur),
+            })
+    }
+    pub fn any(&self, t_min: f64, t_max: f64) -> bool {
+        self.iter().any(|(t, _)| *t >= t_min && *t <= t_max)
+    }
+}
+
+// a t^2 + 2 half_b t + c = 0 的实根, a为0时退化为一次方程, 只有一个根
+pub fn solve_quadratic(a: f64, half_b: f64, c: f64) -> impl Iterator<Item = f64> {
+    let (t0, t1) = if a.abs() < 1e-12 {
+        if half_b.abs() < 1e-12 {
+            (None, None)
+        } else {
+            (Some(-c / (2. * half_b)), None)
+        }
+    } else {
+        let discrim = half_b * half_b - a * c;
+        if discrim < 0. {
+            (None, None)
+        } else {
+            let sqrtd = discrim.sqrt();
+            (Some((-half_b - sqrtd) / a), Some((-half_b + sqrtd) / a))
+        }
+    };
+    t0.into_iter().chain(t1)
+}
+
+// x^3 + a x^2 + b x + c = 0 的一个实根
+fn solve_cubic(a: f64, b: f64, c: f64) -> f64 {
+    // 代换 x = y - a / 3 得到 y^3 + 3p y + 2q = 0
+    let p = (3. * b - a * a) / 9.;
+    let q = (2. * a * a * a - 9. * a * b + 27. * c) / 54.;
+    let d = q * q + p * p * p;
+
+    let y = if d < 0. {
+        let phi = (-q / (-p * p * p).sqrt()).acos() / 3.;
+        2. * (-p).sqrt() * phi.cos()
+    } else {
+        let sqrtd = d.sqrt();
+        (sqrtd - q).cbrt() - (sqrtd + q).cbrt()
+    };
+    y - a / 3.
+}
+
+// c[4] x^4 + c[3] x^3 + c[2] x^2 + c[1] x + c[0] = 0 的实根 (Ferrari)
+pub fn solve_quartic(c: [f64; 5], part: usize) -> Roots {
+    let mut roots = Roots::default();
+    let a = c[3] / c[4];
+    let b = c[2] / c[4];
+    let cc = c[1] / c[4];
+    let d = c[0] / c[4];
+
+    // 代换 x = y - a / 4 得到 y^4 + p y^2 + q y + r = 0
+    let sq_a = a * a;
+    let p = -3. / 8. * sq_a + b;
+    let q = sq_a * a / 8. - a * b / 2. + cc;
+    let r = -3. / 256. * sq_a * sq_a + sq_a * b / 16. - a * cc / 4. + d;
+
+    let mut push_quadratic = |b: f64, c: f64| {
+        for y in solve_quadratic(1., b / 2., c) {
+            roots.push(y - a / 4., part);
+        }
+    };
+
+    if q.abs() < 1e-12 {
+        // 双二次方程 y^4 + p y^2 + r = 0
+        for z in solve_quadratic(1., p / 2., r) {
+            if z >= 0. {
+                push_quadratic(0., -z);
+            }
+        }
+        return roots;
+    }
+
+    // 预解式 z^3 - p/2 z^2 - r z + (r p / 2 - q^2 / 8) = 0
+    let z = solve_cubic(-p / 2., -r, r * p / 2. - q * q / 8.);
+    let u = z * z - r;
+    let v = 2. * z - p;
+    if u < 0. || v < 0. {
+        return roots;
+    }
+    let u = u.sqrt();
+    let v = if q < 0. { -v.sqrt() } else { v.sqrt() };
+    push_quadratic(v, z - u);
+    push_quadratic(-v, z + u);
+    roots
+}
+
+// 绕y轴的角度, 映射到[0, 1]
+pub fn azimuth(p: &Point3) -> f64 {
+    (p.z().atan2(p.x()) + PI) / (2. * PI)
+}
+
+// 在表面上按面积均匀采样时, 方向的pdf = 所有交点 dist^2 / (cos * area) 之和
+pub fn area_pdf_value<F>(roots: &Roots, r: &Ray, area: f64, normal: F) -> f64
+where
+    F: Fn(&Point3, usize) -> Vec3,
+{
+    let v = r.direction();
+    roots
+        .iter()
+        .filter(|(t, _)| *t > 0.001)
+        .map(|&(t, part)| {
+            let distance_squared = t * t * v.len_square();
+            let cos = Vec3::dot(&v, &normal(&r.at(t), part)).abs() / v.len();
+            distance_squared / (cos * area)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(roots: &Roots) -> Vec<f64> {
+        let mut t: Vec<f64> = roots.iter().map(|(t, _)| *t).collect();
+        t.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        t
+    }
+    fn assert_roots(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn quadratic() {
+        // (t - 1)(t - 3) = t^2 - 4t + 3
+        let roots: Vec<f64> = solve_quadratic(1., -2., 3.).collect();
+        assert_roots(&roots, &[1., 3.]);
+        assert_eq!(solve_quadratic(1., 0., 1.).count(), 0);
+    }
+
+    #[test]
+    fn quadratic_degenerates_to_linear() {
+        // 2t - 4 = 0 只有一个根
+        let roots: Vec<f64> = solve_quadratic(0., 1., -4.).collect();
+        assert_roots(&roots, &[2.]);
+        assert_eq!(solve_quadratic(0., 0., 1.).count(), 0);
+    }
+
+    #[test]
+    fn quartic() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        let roots = solve_quartic([24., -50., 35., -10., 1.], 0);
+        assert_roots(&sorted(&roots), &[1., 2., 3., 4.]);
+        // (x^2 - 1)(x^2 - 4), q = 0 的双二次情形
+        let roots = solve_quartic([4., 0., -5., 0., 1.], 0);
+        assert_roots(&sorted(&roots), &[-2., -1., 1., 2.]);
+        // (x - 1)(x + 2)(x^2 + 1)
+        let roots = solve_quartic([-2., 1., -1., 1., 1.], 0);
+        assert_roots(&sorted(&roots), &[-2., 1.]);
+        // x^4 + 1 没有实根
+        assert_eq!(solve_quartic([1., 0., 0., 0., 1.], 0).iter().count(), 0);
+    }
+
+    #[test]
+    fn nearest_root_in_range() {
+        let mut roots = Roots::default();
+        roots.push(3., 0);
+        roots.push(-1., 1);
+        roots.push(2., 2);
+        assert_eq!(roots.nearest(0., 10.), Some((2., 2)));
+        assert_eq!(roots.nearest(2.5, 10.), Some((3., 0)));
+        assert_eq!(roots.nearest(4., 10.), None);
+        assert!(roots.any(-2., 0.));
+        assert!(!roots.any(3.5, 10.));
+    }
+}
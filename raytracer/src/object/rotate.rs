@@ -3,7 +3,7 @@ use std::f64::INFINITY;
 
 use crate::{
     basic::degree_to_radians,
-    bvh::aabb::AABB,
+    bvh::{aabb::AABB, stats::BvhStats},
     Hit::{Hittable, Point3, Ray, Vec3},
 };
 
@@ -22,9 +22,34 @@ impl<T: Hittable> Rotatey<T> {
         let sin_theta = radians.sin();
         let cos_theta = radians.cos();
 
-        let bbox = ptr.bounding_box(0., 1.).unwrap();
         let hasbox = true;
+        let bbox = Self::rotated_box(ptr.bounding_box(0., 1.).unwrap(), sin_theta, cos_theta);
 
+        Self {
+            ptr,
+            sin_theta,
+            cos_theta,
+            hasbox,
+            bbox,
+        }
+    }
+
+    // 将光线转到物体本身的坐标系下
+    fn rotate_ray(&self, r: &Ray) -> Ray {
+        let mut origin = r.origin();
+        let mut direction = r.direction();
+
+        origin[0] = self.cos_theta * r.origin()[0] - self.sin_theta * r.origin()[2];
+        origin[2] = self.sin_theta * r.origin()[0] + self.cos_theta * r.origin()[2];
+
+        direction[0] = self.cos_theta * r.direction()[0] - self.sin_theta * r.direction()[2];
+        direction[2] = self.sin_theta * r.direction()[0] + self.cos_theta * r.direction()[2];
+
+        Ray::new(origin, direction, r.time())
+    }
+
+    // 旋转后原包围盒8个顶点的包围盒
+    fn rotated_box(bbox: AABB, sin_theta: f64, cos_theta: f64) -> AABB {
         let mut mi = Point3::new(INFINITY, INFINITY, INFINITY);
         let mut mx = Point3::new(-INFINITY, -INFINITY, -INFINITY);
 
@@ -48,29 +73,13 @@ impl<T: Hittable> Rotatey<T> {
             }
         }
 
-        let bbox = AABB::new(mi, mx);
-        Self {
-            ptr,
-            sin_theta,
-            cos_theta,
-            hasbox,
-            bbox,
-        }
+        AABB::new(mi, mx)
     }
 }
 
 impl<T: Hittable> Hittable for Rotatey<T> {
     fn hit(&self, r: &crate::Hit::Ray, t_min: f64, t_max: f64) -> Option<crate::Hit::HitRecord> {
-        let mut origin = r.origin();
-        let mut direction = r.direction();
-
-        origin[0] = self.cos_theta * r.origin()[0] - self.sin_theta * r.origin()[2];
-        origin[2] = self.sin_theta * r.origin()[0] + self.cos_theta * r.origin()[2];
-
-        direction[0] = self.cos_theta * r.direction()[0] - self.sin_theta * r.direction()[2];
-        direction[2] = self.sin_theta * r.direction()[0] + self.cos_theta * r.direction()[2];
-
-        let rotated_r = Ray::new(origin, direction, r.time());
+        let rotated_r = self.rotate_ray(r);
 
         if let Some(mut rec) = self.ptr.hit(&rotated_r, t_min, t_max) {
             let mut p = rec.p;
@@ -82,8 +91,9 @@ impl<T: Hittable> Hittable for Rotatey<T> {
             normal[0] = self.cos_theta * rec.normal[0] + self.sin_theta * rec.normal[2];
             normal[2] = -self.sin_theta * rec.normal[0] + self.cos_theta * rec.normal[2];
 
+            // 旋转不改变法向与光线方向的点积符号, 保留原来的 front_face
             rec.p = p;
-            rec.set_face_normal(&rotated_r, &normal);
+            rec.normal = normal;
 
             Some(rec)
         } else {
@@ -91,7 +101,48 @@ impl<T: Hittable> Hittable for Rotatey<T> {
         }
     }
 
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.ptr.occluded(&self.rotate_ray(r), t_min, t_max)
+    }
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        self.ptr.transmittance(&self.rotate_ray(r), t_min, t_max)
+    }
+    // 旋转不改变立体角, 只需转换方向
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        let rotated_r = self.rotate_ray(&Ray::new(*o, *v, 0.));
+        self.ptr
+            .pdf_value(&rotated_r.origin(), &rotated_r.direction())
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        let rotated_o = self.rotate_ray(&Ray::new(*o, Vec3::default(), 0.)).origin();
+        let local = self.ptr.random(&rotated_o);
+        Vec3::new(
+            self.cos_theta * local.x + self.sin_theta * local.z,
+            local.y,
+            -self.sin_theta * local.x + self.cos_theta * local.z,
+        )
+    }
+    fn light_samplable(&self) -> bool {
+        self.ptr.light_samplable()
+    }
+    fn power(&self) -> Option<f64> {
+        self.ptr.power()
+    }
+
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
         Some(self.bbox)
     }
+
+    fn refit(&mut self, time0: f64, time1: f64) {
+        self.ptr.refit(time0, time1);
+        let bbox = self.ptr.bounding_box(time0, time1).unwrap();
+        self.bbox = Self::rotated_box(bbox, self.sin_theta, self.cos_theta);
+    }
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        self.ptr.sah_cost(time0, time1)
+    }
+    fn bvh_stats(&self, depth: usize, stats: &mut BvhStats) -> bool {
+        stats.memory += std::mem::size_of::<Self>() - std::mem::size_of::<T>();
+        self.ptr.bvh_stats(depth, stats)
+    }
 }
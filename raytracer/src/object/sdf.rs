@@ -0,0 +1,77 @@
+use crate::{
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    sdf::SDF,
+    Hit::{HitRecord, Hittable, Material, Ray, Vec3},
+};
+
+const MAX_STEPS: usize = 512;
+
+// 用 sphere tracing 求交的距离场物体, 距离场本身没有边界, 需要给定包围盒
+pub struct SdfObject<S: SDF, M: Material> {
+    pub sdf: S,
+    pub bbox: AABB,
+    pub mat: M,
+    eps: f64, // 距离小于eps即认为到达表面, 取包围盒对角线的比例
+}
+
+impl<S: SDF, M: Material> SdfObject<S, M> {
+    pub fn new(sdf: S, bbox: AABB, mat: M) -> Self {
+        let eps = (bbox.maxi - bbox.mini).len() * 1e-5;
+        Self {
+            sdf,
+            bbox,
+            mat,
+            eps,
+        }
+    }
+
+    fn march(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<f64> {
+        let (t0, t1) = self.bbox.clip(r, t_min, t_max)?;
+        let len = r.direction().len();
+
+        // 从表面出发的光线(反射/折射)先离开表面, 再根据所在的一侧决定符号
+        let mut t = t0;
+        let mut d = self.sdf.distance(&r.at(t));
+        for _ in 0..16 {
+            if d.abs() >= self.eps {
+                break;
+            }
+            t += self.eps / len;
+            d = self.sdf.distance(&r.at(t));
+        }
+        let sign = if d < 0. { -1. } else { 1. };
+
+        for _ in 0..MAX_STEPS {
+            if t > t1 {
+                return None;
+            }
+            let d = sign * self.sdf.distance(&r.at(t));
+            if d < self.eps {
+                return Some(t);
+            }
+            t += d / len;
+        }
+        None
+    }
+}
+
+impl<S: SDF, M: Material> Hittable for SdfObject<S, M> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
+        let t = self.march(r, t_min, t_max)?;
+        let p = r.at(t);
+        let outward_normal = self.sdf.normal(&p, self.eps);
+
+        let mut rec = HitRecord::new(t, p, Vec3::default(), bool::default(), &self.mat, 0., 0.);
+        rec.set_face_normal(r, &outward_normal);
+        Some(rec)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        self.march(r, t_min, t_max).is_some()
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        Some(self.bbox)
+    }
+}
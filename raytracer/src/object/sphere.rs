@@ -5,7 +5,14 @@ pub use crate::basic::{
     RAY::Ray,
     VEC3::{Point3, Vec3},
 };
-use crate::{bvh::aabb::AABB, material::ONB, pdf::random_to_sphere, Hit::Material};
+use crate::{
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    light::area_power,
+    material::ONB,
+    object::csg::{Interval, Solid},
+    pdf::random_to_sphere,
+    Hit::Material,
+};
 
 pub struct Sphere<M: Material> {
     pub center: Point3,
@@ -36,10 +43,29 @@ impl<M: Material> Sphere<M> {
 
         Some([phi / 2. / PI, theta / PI])
     }
+    fn record(&self, r: &Ray, t: f64) -> HitRecord {
+        let outward_normal = (r.at(t) - self.center) / self.radius;
+        let tup = Self::get_sphere_uv(&outward_normal).unwrap();
+
+        let mut rec = HitRecord::new(
+            t,
+            r.at(t),
+            Vec3::default(),
+            bool::default(),
+            &self.mat,
+            tup[0],
+            tup[1],
+        );
+
+        rec.set_face_normal(r, &outward_normal);
+
+        rec
+    }
 }
 
 impl<M: Material> Hittable for Sphere<M> {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
         let oc = r.origin() - self.center;
         let a = r.direction().len_square();
         let half_b = Vec3::dot(&oc, &r.direction());
@@ -60,22 +86,26 @@ impl<M: Material> Hittable for Sphere<M> {
             }
         }
 
-        let outward_normal = (r.at(root) - self.center) / self.radius;
-        let tup = Self::get_sphere_uv(&outward_normal).unwrap();
+        Some(self.record(r, root))
+    }
 
-        let mut rec = HitRecord::new(
-            root,
-            r.at(root),
-            Vec3::default(),
-            bool::default(),
-            &self.mat,
-            tup[0],
-            tup[1],
-        );
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        let oc = r.origin() - self.center;
+        let a = r.direction().len_square();
+        let half_b = Vec3::dot(&oc, &r.direction());
+        let c = oc.len_square() - self.radius * self.radius;
 
-        rec.set_face_normal(r, &outward_normal);
+        let discrim = half_b.powi(2) - a * c;
+        if discrim < 0. {
+            return false;
+        }
 
-        Some(rec)
+        // 两个根中有一个在范围内即可, 不需要计算uv和法向
+        let sqrtd = discrim.sqrt();
+        let root0 = (-half_b - sqrtd) / a;
+        let root1 = (-half_b + sqrtd) / a;
+        (root0 >= t_min && root0 <= t_max) || (root1 >= t_min && root1 <= t_max)
     }
 
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
@@ -99,4 +129,33 @@ impl<M: Material> Hittable for Sphere<M> {
         let uvw = ONB::build(&direction);
         uvw.local(random_to_sphere(self.radius, distance_squared))
     }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    fn power(&self) -> Option<f64> {
+        area_power(&self.mat, &self.center, 4. * PI * self.radius * self.radius)
+    }
+}
+
+impl<M: Material> Solid for Sphere<M> {
+    fn intervals(&self, r: &Ray, t_min: f64, t_max: f64) -> Vec<Interval> {
+        let oc = r.origin() - self.center;
+        let a = r.direction().len_square();
+        let half_b = Vec3::dot(&oc, &r.direction());
+        let c = oc.len_square() - self.radius * self.radius;
+
+        let discrim = half_b.powi(2) - a * c;
+        if discrim <= 0. {
+            return Vec::new();
+        }
+        let sqrtd = discrim.sqrt();
+        let (enter, exit) = ((-half_b - sqrtd) / a, (-half_b + sqrtd) / a);
+        if exit < t_min || enter > t_max {
+            return Vec::new();
+        }
+        vec![Interval {
+            enter: self.record(r, enter),
+            exit: self.record(r, exit),
+        }]
+    }
 }
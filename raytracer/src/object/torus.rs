@@ -0,0 +1,146 @@
+use std::f64::consts::PI;
+
+use crate::{
+    basic::random_double,
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    light::area_power,
+    Hit::{HitRecord, Hittable, Material, Point3, Ray, Vec3},
+};
+
+use super::quadric::{area_pdf_value, azimuth, solve_quadratic, solve_quartic, Roots};
+
+// 圆环: 中心 center, 绕 y 轴; major_radius 为环心圆半径, minor_radius 为管半径
+pub struct Torus<M: Material> {
+    pub center: Point3,
+    pub major_radius: f64,
+    pub minor_radius: f64,
+    pub mat: M,
+}
+
+impl<M: Material> Torus<M> {
+    pub fn new(center: Point3, major_radius: f64, minor_radius: f64, mat: M) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            mat,
+        }
+    }
+
+    // 四次多项式 (|p|^2 + R^2 - r^2)^2 - 4 R^2 (x^2 + z^2) 关于 t 的系数, d 为单位向量
+    fn coefficients(&self, o: &Vec3, d: &Vec3) -> [f64; 5] {
+        let big = self.major_radius * self.major_radius;
+        let n = Vec3::dot(o, d);
+        let k = o.len_square() + big - self.minor_radius * self.minor_radius;
+        [
+            k * k - 4. * big * (o.x() * o.x() + o.z() * o.z()),
+            4. * n * k - 8. * big * (o.x() * d.x() + o.z() * d.z()),
+            4. * n * n + 2. * k - 4. * big * (d.x() * d.x() + d.z() * d.z()),
+            4. * n,
+            1.,
+        ]
+    }
+
+    fn roots(&self, r: &Ray) -> Roots {
+        let len = r.direction().len();
+        let d = r.direction() / len;
+        let o = r.origin() - self.center;
+
+        // 先用包围球排除, 再把起点移到离中心最近处以减小四次方程的误差
+        let bound = self.major_radius + self.minor_radius;
+        let shift = -Vec3::dot(&o, &d);
+        if solve_quadratic(1., -shift, o.len_square() - bound * bound)
+            .next()
+            .is_none()
+        {
+            return Roots::default();
+        }
+        let o = o + shift * d;
+        let c = self.coefficients(&o, &d);
+
+        let mut roots = Roots::default();
+        for &(s, part) in solve_quartic(c, 0).iter() {
+            // 牛顿迭代修正
+            let mut s = s;
+            for _ in 0..2 {
+                let f = (((c[4] * s + c[3]) * s + c[2]) * s + c[1]) * s + c[0];
+                let df = ((4. * c[4] * s + 3. * c[3]) * s + 2. * c[2]) * s + c[1];
+                if df.abs() > 1e-12 {
+                    s -= f / df;
+                }
+            }
+            roots.push((s + shift) / len, part);
+        }
+        roots
+    }
+    fn surface(&self, p: &Vec3) -> (Vec3, f64, f64) {
+        let rho = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        // 管中心圆上最近的点
+        let ring = if rho > 0. {
+            Vec3::new(p.x(), 0., p.z()) * (self.major_radius / rho)
+        } else {
+            Vec3::new(self.major_radius, 0., 0.)
+        };
+        let outward_normal = (*p - ring) / self.minor_radius;
+        let v = (p.y().atan2(rho - self.major_radius) + PI) / (2. * PI);
+        (outward_normal, azimuth(p), v)
+    }
+    fn area(&self) -> f64 {
+        4. * PI * PI * self.major_radius * self.minor_radius
+    }
+}
+
+impl<M: Material> Hittable for Torus<M> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
+        let (t, _) = self.roots(r).nearest(t_min, t_max)?;
+        let p = r.at(t);
+        let (outward_normal, u, v) = self.surface(&(p - self.center));
+
+        let mut rec = HitRecord::new(t, p, Vec3::default(), bool::default(), &self.mat, u, v);
+        rec.set_face_normal(r, &outward_normal);
+        Some(rec)
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        self.roots(r).any(t_min, t_max)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
+        let outer = self.major_radius + self.minor_radius;
+        let half = Vec3::new(outer, self.minor_radius, outer);
+        Some(AABB::new(self.center - half, self.center + half))
+    }
+
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        let r = Ray::new(*o, *v, 0.);
+        area_pdf_value(&self.roots(&r), &r, self.area(), |p, _| {
+            self.surface(&(*p - self.center)).0
+        })
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        // 面元正比于 R + r cos(theta), 对 theta 拒绝采样
+        let theta = loop {
+            let theta = 2. * PI * random_double();
+            let accept = (self.major_radius + self.minor_radius * theta.cos())
+                / (self.major_radius + self.minor_radius);
+            if random_double() < accept {
+                break theta;
+            }
+        };
+        let phi = 2. * PI * random_double();
+        let rho = self.major_radius + self.minor_radius * theta.cos();
+        let p = Vec3::new(
+            rho * phi.cos(),
+            self.minor_radius * theta.sin(),
+            rho * phi.sin(),
+        );
+        self.center + p - *o
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    fn power(&self) -> Option<f64> {
+        area_power(&self.mat, &self.center, self.area())
+    }
+}
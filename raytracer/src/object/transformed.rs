@@ -0,0 +1,89 @@
+use crate::{
+    basic::transform::Transform,
+    bvh::{aabb::AABB, stats::BvhStats},
+    object::csg::{Interval, Solid},
+    Hit::{HitRecord, Hittable, Point3, Ray, Vec3},
+};
+
+// 对物体做任意仿射变换(平移/旋转/缩放/错切及其复合)
+pub struct Transformed<H: Hittable> {
+    ptr: H,
+    transform: Transform, // 物体空间 -> 世界空间
+}
+
+impl<H: Hittable> Transformed<H> {
+    pub fn new(ptr: H, transform: Transform) -> Self {
+        Self { ptr, transform }
+    }
+    fn to_world<'a>(&self, mut rec: HitRecord<'a>) -> HitRecord<'a> {
+        // 变换保持法向与光线方向的点积符号, front_face 不变
+        rec.p = self.transform.point(&rec.p);
+        rec.normal = self.transform.normal(&rec.normal).unit_vector();
+        rec
+    }
+}
+
+impl<H: Hittable> Hittable for Transformed<H> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let local_r = self.transform.inverse().ray(r);
+        self.ptr
+            .hit(&local_r, t_min, t_max)
+            .map(|rec| self.to_world(rec))
+    }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.ptr
+            .occluded(&self.transform.inverse().ray(r), t_min, t_max)
+    }
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        self.ptr
+            .transmittance(&self.transform.inverse().ray(r), t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AABB> {
+        self.ptr
+            .bounding_box(time0, time1)
+            .map(|bbox| self.transform.bounding_box(&bbox))
+    }
+
+    fn pdf_value(&self, o: &Point3, v: &Vec3) -> f64 {
+        // 物体空间中的立体角密度换算到世界空间
+        let local_o = self.transform.m_inv.point(o);
+        let (local_v, jacobian) = self.transform.local_direction(v);
+        self.ptr.pdf_value(&local_o, &local_v) * jacobian
+    }
+    fn random(&self, o: &Vec3) -> Vec3 {
+        let local_o = self.transform.m_inv.point(o);
+        self.transform.vector(&self.ptr.random(&local_o))
+    }
+    fn light_samplable(&self) -> bool {
+        self.ptr.light_samplable()
+    }
+    // 发光亮度不变, 功率随表面积缩放
+    fn power(&self) -> Option<f64> {
+        Some(self.ptr.power()? * self.transform.area_scale())
+    }
+
+    fn refit(&mut self, time0: f64, time1: f64) {
+        self.ptr.refit(time0, time1);
+    }
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        self.ptr.sah_cost(time0, time1)
+    }
+    fn bvh_stats(&self, depth: usize, stats: &mut BvhStats) -> bool {
+        stats.memory += std::mem::size_of::<Self>() - std::mem::size_of::<H>();
+        self.ptr.bvh_stats(depth, stats)
+    }
+}
+
+impl<H: Solid> Solid for Transformed<H> {
+    fn intervals(&self, r: &Ray, t_min: f64, t_max: f64) -> Vec<Interval> {
+        self.ptr
+            .intervals(&self.transform.inverse().ray(r), t_min, t_max)
+            .into_iter()
+            .map(|i| Interval {
+                enter: self.to_world(i.enter),
+                exit: self.to_world(i.exit),
+            })
+            .collect()
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    bvh::aabb::AABB,
+    bvh::{aabb::AABB, stats::BvhStats},
     Hit::{Hittable, Ray, Vec3},
 };
 
@@ -21,14 +21,20 @@ impl<H: Hittable> Hittable for Translate<H> {
         let moved_r = Ray::new(r.origin() - self.offset, r.direction(), r.time());
         if let Some(mut rec) = self.ptr.hit(&moved_r, t_min, t_max) {
             rec.p += self.offset;
-            let outward = rec.normal;
-            rec.set_face_normal(&moved_r, &outward);
 
             Some(rec)
         } else {
             None
         }
     }
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        let moved_r = Ray::new(r.origin() - self.offset, r.direction(), r.time());
+        self.ptr.occluded(&moved_r, t_min, t_max)
+    }
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let moved_r = Ray::new(r.origin() - self.offset, r.direction(), r.time());
+        self.ptr.transmittance(&moved_r, t_min, t_max)
+    }
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<crate::bvh::aabb::AABB> {
         if let Some(output_box) = self.ptr.bounding_box(time0, time1) {
             let output_box =
@@ -41,8 +47,24 @@ impl<H: Hittable> Hittable for Translate<H> {
     fn random(&self, origin: &Vec3) -> Vec3 {
         self.ptr.random(&(*origin - self.offset))
     }
+    fn light_samplable(&self) -> bool {
+        self.ptr.light_samplable()
+    }
     fn pdf_value(&self, o: &crate::Hit::Point3, v: &Vec3) -> f64 {
         self.ptr.pdf_value(&(*o - self.offset), v)
         // println!("{:?} {:?} res = {}", *o - self.offset, v, res);
     }
+    fn power(&self) -> Option<f64> {
+        self.ptr.power()
+    }
+    fn refit(&mut self, time0: f64, time1: f64) {
+        self.ptr.refit(time0, time1);
+    }
+    fn sah_cost(&self, time0: f64, time1: f64) -> f64 {
+        self.ptr.sah_cost(time0, time1)
+    }
+    fn bvh_stats(&self, depth: usize, stats: &mut BvhStats) -> bool {
+        stats.memory += std::mem::size_of::<Self>() - std::mem::size_of::<H>();
+        self.ptr.bvh_stats(depth, stats)
+    }
 }
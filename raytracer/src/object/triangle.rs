@@ -1,12 +1,16 @@
 #![allow(clippy::many_single_char_names)]
+use std::f64::INFINITY;
+
 use crate::{
-    bvh::aabb::AABB,
-    material::{HitRecord, Material, Point3, Vec3},
+    basic::{random_double, transform::Transform},
+    bvh::{aabb::AABB, stats::count_primitive_test},
+    material::{HitRecord, Material, Point3, Ray, Vec3},
     Hit::Hittable,
 };
 
 const EPS: f64 = 1e-10;
 
+#[derive(Clone)]
 pub struct Triangle<M: Material> {
     pub v0: Point3,
     pub v1: Point3,
@@ -27,10 +31,27 @@ impl<M: Material> Triangle<M> {
             && Vec3::dot(&n, &(self.v2 - self.v1).cross(p - self.v1)) >= 0.
             && Vec3::dot(&n, &(self.v0 - self.v2).cross(p - self.v2)) >= 0.
     }
+    pub fn area(&self) -> f64 {
+        0.5 * (self.v1 - self.v0).cross(self.v2 - self.v0).len()
+    }
+
+    // 直接变换顶点得到世界空间中的三角形(如作为光源加入 lights)
+    pub fn transformed(&self, transform: &Transform) -> Self
+    where
+        M: Clone,
+    {
+        Self::new(
+            transform.point(&self.v0),
+            transform.point(&self.v1),
+            transform.point(&self.v2),
+            self.mat.clone(),
+        )
+    }
 }
 
 impl<M: Material> Hittable for Triangle<M> {
     fn hit(&self, r: &crate::material::Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        count_primitive_test();
         let n = self.get_normal();
         let NdotRaydir = n.dot(&r.direction());
         if NdotRaydir.abs() < EPS {
@@ -47,15 +68,12 @@ impl<M: Material> Hittable for Triangle<M> {
             return None;
         }
 
-        let a1 = self.v0.x - self.v1.x;
-        let b1 = self.v0.x - self.v2.x;
-        let c1 = self.v0.x - p.x;
-        let a2 = self.v0.y - self.v1.y;
-        let b2 = self.v0.y - self.v2.y;
-        let c2 = self.v0.y - p.y;
-
-        let beta = (c1 * b2 - b1 * c2) / (a1 * b2 - b1 * a2);
-        let gamma = (a1 * c2 - a2 * c1) / (a1 * b2 - b1 * a2);
+        // p = v0 + beta * e1 + gamma * e2, 用叉积求解(只投影到xy平面时, 平行于z轴的三角形会退化)
+        let e1 = self.v1 - self.v0;
+        let e2 = self.v2 - self.v0;
+        let cross = e1.cross(e2);
+        let beta = Vec3::dot(&(p - self.v0).cross(e2), &cross) / cross.len_square();
+        let gamma = Vec3::dot(&e1.cross(p - self.v0), &cross) / cross.len_square();
 
         let mut rec = HitRecord::new(
             t,
@@ -70,6 +88,19 @@ impl<M: Material> Hittable for Triangle<M> {
         Some(rec)
     }
 
+    fn occluded(&self, r: &crate::material::Ray, t_min: f64, t_max: f64) -> bool {
+        count_primitive_test();
+        // 不需要重心坐标
+        let n = self.get_normal();
+        let n_dot_dir = n.dot(&r.direction());
+        if n_dot_dir.abs() < EPS {
+            return false;
+        }
+        let t = Vec3::dot(&(self.v0 - r.orig), &n) / n_dot_dir;
+
+        t >= t_min && t <= t_max && self.inside(r.at(t))
+    }
+
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AABB> {
         let eps = Point3::new(EPS, EPS, EPS);
         Some(AABB::new(
@@ -85,4 +116,39 @@ impl<M: Material> Hittable for Triangle<M> {
             ) + eps,
         ))
     }
+
+    fn pdf_value(&self, origin: &Point3, v: &Vec3) -> f64 {
+        let rec = match self.hit(&Ray::new(*origin, *v, 0.), 0.001, INFINITY) {
+            Some(rec) => rec,
+            None => return 0.,
+        };
+        let distance_squared = rec.t.powi(2) * v.len_square();
+        let cos = Vec3::dot(v, &rec.normal).abs() / v.len();
+        // 掠射时密度趋于无穷, 直接视为采样不到
+        if cos < 1e-8 {
+            return 0.;
+        }
+
+        distance_squared / (cos * self.area())
+    }
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        // 面积均匀采样的重心坐标
+        let su = random_double().sqrt();
+        let b0 = 1. - su;
+        let b1 = random_double() * su;
+        let point = b0 * self.v0 + b1 * self.v1 + (1. - b0 - b1) * self.v2;
+        point - *origin
+    }
+    fn light_samplable(&self) -> bool {
+        true
+    }
+    // 发光贴图在三个顶点与重心处的平均亮度代表整个三角形
+    fn power(&self) -> Option<f64> {
+        let mut luminance = 0.;
+        for &(beta, gamma) in [(0., 0.), (1., 0.), (0., 1.), (1. / 3., 1. / 3.)].iter() {
+            let p = (1. - beta - gamma) * self.v0 + beta * self.v1 + gamma * self.v2;
+            luminance += self.mat.emitted(beta, gamma, &p)?.luminance() / 4.;
+        }
+        Some(self.mat.emitted_cosine_integral() * luminance * self.area())
+    }
 }
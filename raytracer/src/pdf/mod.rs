@@ -5,6 +5,7 @@ use crate::Hit::{random_double, Vec3};
 pub mod cospdf;
 pub mod hittablepdf;
 pub mod mixturepdf;
+pub mod phase;
 
 pub trait PDF {
     fn generate(&self) -> Vec3;
@@ -0,0 +1,203 @@
+// 介质中的相函数: 入射方向(光线传播方向)与出射方向夹角 theta 的分布, 关于立体角归一化
+use std::f64::consts::PI;
+
+use crate::{
+    material::ONB,
+    Hit::{random_double, Vec3},
+};
+
+use super::PDF;
+
+pub trait PhaseFunction: Send + Sync {
+    fn value(&self, cos_theta: f64) -> f64;
+    // 按 value 的分布采样 cos_theta, 方位角均匀
+    fn sample_cos(&self) -> f64;
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct IsotropicPhase;
+
+impl PhaseFunction for IsotropicPhase {
+    fn value(&self, _cos_theta: f64) -> f64 {
+        1. / (4. * PI)
+    }
+    fn sample_cos(&self) -> f64 {
+        1. - 2. * random_double()
+    }
+}
+
+// g > 0 前向散射, g < 0 后向散射, g = 0 为各向同性
+#[derive(Clone, Copy)]
+pub struct HenyeyGreenstein {
+    pub g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(g: f64) -> Self {
+        Self {
+            g: g.clamp(-0.999, 0.999),
+        }
+    }
+}
+
+impl PhaseFunction for HenyeyGreenstein {
+    fn value(&self, cos_theta: f64) -> f64 {
+        let g = self.g;
+        let denom = 1. + g * g - 2. * g * cos_theta;
+        (1. - g * g) / (4. * PI * denom * denom.sqrt())
+    }
+    fn sample_cos(&self) -> f64 {
+        let g = self.g;
+        let xi = random_double();
+        if g.abs() < 1e-3 {
+            return 1. - 2. * xi;
+        }
+        let sq = (1. - g * g) / (1. - g + 2. * g * xi);
+        ((1. + g * g - sq * sq) / (2. * g)).clamp(-1., 1.)
+    }
+}
+
+// 两个HG的加权和, 常用于云(强前向 + 弱后向)
+#[derive(Clone, Copy)]
+pub struct DoubleHenyeyGreenstein {
+    pub forward: HenyeyGreenstein,
+    pub backward: HenyeyGreenstein,
+    pub weight: f64, // forward 所占的比例
+}
+
+impl DoubleHenyeyGreenstein {
+    pub fn new(g_forward: f64, g_backward: f64, weight: f64) -> Self {
+        Self {
+            forward: HenyeyGreenstein::new(g_forward),
+            backward: HenyeyGreenstein::new(g_backward),
+            weight,
+        }
+    }
+}
+
+impl PhaseFunction for DoubleHenyeyGreenstein {
+    fn value(&self, cos_theta: f64) -> f64 {
+        self.weight * self.forward.value(cos_theta)
+            + (1. - self.weight) * self.backward.value(cos_theta)
+    }
+    fn sample_cos(&self) -> f64 {
+        if random_double() < self.weight {
+            self.forward.sample_cos()
+        } else {
+            self.backward.sample_cos()
+        }
+    }
+}
+
+// 瑞利散射: 3 / (16 pi) * (1 + cos^2)
+#[derive(Clone, Copy, Default)]
+pub struct Rayleigh;
+
+impl PhaseFunction for Rayleigh {
+    fn value(&self, cos_theta: f64) -> f64 {
+        3. / (16. * PI) * (1. + cos_theta * cos_theta)
+    }
+    fn sample_cos(&self) -> f64 {
+        // 反解分布函数 (mu^3 + 3 mu + 4) / 8 = xi
+        let q = 4. * random_double() - 2.;
+        let s = (q * q + 1.).sqrt();
+        ((q + s).cbrt() + (q - s).cbrt()).clamp(-1., 1.)
+    }
+}
+
+// 以入射方向为轴的相函数采样
+pub struct PhasePDF<P: PhaseFunction> {
+    phase: P,
+    uvw: ONB,
+}
+
+impl<P: PhaseFunction> PhasePDF<P> {
+    pub fn new(phase: P, direction: &Vec3) -> Self {
+        Self {
+            phase,
+            uvw: ONB::build(direction),
+        }
+    }
+}
+
+impl<P: PhaseFunction> PDF for PhasePDF<P> {
+    fn value(&self, direction: &Vec3) -> f64 {
+        let cos = Vec3::dot(&direction.unit_vector(), &self.uvw.w());
+        self.phase.value(cos)
+    }
+    fn generate(&self) -> Vec3 {
+        let cos = self.phase.sample_cos();
+        let sin = (1. - cos * cos).max(0.).sqrt();
+        let phi = 2. * PI * random_double();
+        self.uvw
+            .local(Vec3::new(sin * phi.cos(), sin * phi.sin(), cos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: usize = 200_000;
+
+    // 在球面上对 value 积分应为 1
+    fn integral<P: PhaseFunction>(phase: &P) -> f64 {
+        let n = 10_000;
+        (0..n)
+            .map(|i| {
+                let cos = -1. + 2. * (i as f64 + 0.5) / n as f64;
+                phase.value(cos) * 2. * PI * 2. / n as f64
+            })
+            .sum()
+    }
+
+    // 采样得到的 cos 落在 [-1, x] 内的比例与 value 的分布函数比较
+    fn assert_matches_cdf<P: PhaseFunction>(phase: &P) {
+        let samples: Vec<f64> = (0..SAMPLES).map(|_| phase.sample_cos()).collect();
+        for &x in [-0.5, 0., 0.5, 0.9].iter() {
+            let n = 10_000;
+            let expected: f64 = (0..n)
+                .map(|i| {
+                    let cos = -1. + (x + 1.) * (i as f64 + 0.5) / n as f64;
+                    phase.value(cos) * 2. * PI * (x + 1.) / n as f64
+                })
+                .sum();
+            let actual = samples.iter().filter(|&&cos| cos <= x).count() as f64 / SAMPLES as f64;
+            assert!(
+                (actual - expected).abs() < 0.01,
+                "P(cos <= {}) = {}, expected {}",
+                x,
+                actual,
+                expected
+            );
+        }
+        assert!(samples.iter().all(|cos| (-1. ..=1.).contains(cos)));
+    }
+
+    #[test]
+    fn henyey_greenstein() {
+        for &g in [-0.7, 0., 0.3, 0.9].iter() {
+            let hg = HenyeyGreenstein::new(g);
+            assert!((integral(&hg) - 1.).abs() < 1e-3);
+            assert_matches_cdf(&hg);
+            // 平均余弦即为 g
+            let mean = (0..SAMPLES).map(|_| hg.sample_cos()).sum::<f64>() / SAMPLES as f64;
+            assert!((mean - g).abs() < 0.01, "g = {}, mean = {}", g, mean);
+        }
+    }
+
+    #[test]
+    fn double_henyey_greenstein() {
+        let phase = DoubleHenyeyGreenstein::new(0.8, -0.3, 0.7);
+        assert!((integral(&phase) - 1.).abs() < 1e-3);
+        assert_matches_cdf(&phase);
+    }
+
+    #[test]
+    fn rayleigh() {
+        assert!((integral(&Rayleigh) - 1.).abs() < 1e-3);
+        assert_matches_cdf(&Rayleigh);
+        assert!((integral(&IsotropicPhase) - 1.).abs() < 1e-9);
+        assert_matches_cdf(&IsotropicPhase);
+    }
+}
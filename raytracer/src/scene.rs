@@ -1,27 +1,40 @@
 #![allow(unused_imports)]
+use image::RgbImage;
 use rand::{thread_rng, Rng};
 
 use std::{collections::HashMap, sync::Arc};
 
 use crate::{
-    basic::{self, random_range},
+    basic::{
+        self,
+        animation::{AnimatedTransform, Interpolation, Keyframe, Quaternion},
+        random_range,
+        transform::Transform,
+    },
     bvh::{
         aabb::{surrounding_box, AABB},
         bvh_node::BvhNode,
     },
     material::diffuse::DiffuseLight,
     object::{
+        animated::Animated,
         cube::Cube,
         medium::ConstantMedium,
         move_sphere::MoveSphere,
-        rectangle::{Rectanglexy, Rectanglexz, Rectangleyz},
+        quad::Quad,
+        rectangle,
         rotate::{self, Rotatey},
+        transformed::Transformed,
         translate::Translate,
         triangle::Triangle,
     },
     texture::{
-        checker::Checker, image_texture::ImageTexture, obj_texture::ObjTexture,
-        perlin::NoiseTexture, solid_color::SolidColor, Texture,
+        checker::Checker,
+        image_texture::ImageTexture,
+        obj_texture::{ObjEmission, ObjTexture},
+        perlin::NoiseTexture,
+        solid_color::SolidColor,
+        Texture,
     },
     Hit::{self, Hittable, HittableList},
 };
@@ -37,15 +50,73 @@ pub use crate::{
 
 // const ROOTFILE: &str = "obj_material/";
 
+// 带有 Ke/map_Ke 的发光三角形
+pub type EmissiveTriangle = Triangle<DiffuseLight<ObjEmission>>;
+
 pub fn load_obj(
+    world: &mut HittableList,
+    lights: &mut HittableList,
+    rootfile: &str,
+    objfile: &str,
+    transform: Transform,
+) {
+    // objfile: obj格式文件名 transform: 模型空间到世界空间的变换(缩放/旋转/平移)
+    // 发光的三角形以世界坐标建一棵BVH, 作为一个光源加入 lights, 使光源采样的开销不随三角形数量线性增长
+    let (objects, emissive) = load_obj_models(rootfile, objfile);
+    for object in objects {
+        world.add(Arc::new(Transformed::new(object, transform)));
+    }
+    if !emissive.is_empty() {
+        let emissive: Vec<Arc<dyn Hittable>> = emissive
+            .iter()
+            .map(|tri| Arc::new(tri.transformed(&transform)) as Arc<dyn Hittable>)
+            .collect();
+        lights.add(Arc::new(BvhNode::new_from_vec(emissive, 0., 1.)));
+    }
+}
+
+// 关键帧动画(运动模糊)的obj模型, 发光的三角形不作为光源采样
+pub fn load_obj_animated(
     world: &mut HittableList,
     rootfile: &str,
-    rate: f64,
     objfile: &str,
-    offset: Vec3,
-    rotate_angle: f64,
+    animation: AnimatedTransform,
 ) {
-    // rate: 物体放大倍数 objfile: obj格式文件名 imgfile: 贴图文件名
+    for object in load_obj_models(rootfile, objfile).0 {
+        world.add(Arc::new(Animated::new(object, animation.clone())));
+    }
+}
+
+// MTL 中的颜色, 如 "Ke 1.0 0.8 0.6"; 只有一个数时 g, b 与 r 相同
+fn parse_mtl_color(value: &str) -> Color {
+    let c: Vec<f64> = value
+        .split_whitespace()
+        .map(|x| {
+            x.parse()
+                .unwrap_or_else(|_| panic!("invalid number in MTL color \"{}\"", value))
+        })
+        .collect();
+    match c.len() {
+        1 => Color::new(c[0], c[0], c[0]),
+        3 => Color::new(c[0], c[1], c[2]),
+        _ => panic!(
+            "invalid MTL color \"{}\": expected 1 or 3 numbers, found {}",
+            value,
+            c.len()
+        ),
+    }
+}
+
+fn load_texture(filename: &str) -> Arc<RgbImage> {
+    Arc::new(
+        image::open(filename)
+            .expect("load image failed")
+            .into_rgb8(),
+    )
+}
+
+// 每个model建一棵模型空间下的BVH, 并返回模型空间下的发光三角形
+pub fn load_obj_models(rootfile: &str, objfile: &str) -> (Vec<BvhNode>, Vec<EmissiveTriangle>) {
     let obj = tobj::load_obj(
         //"obj_material/10483_baseball_v1_L3.obj",
         String::from(rootfile) + objfile,
@@ -66,21 +137,35 @@ pub fn load_obj(
     // If you don't need the materials, you can generate a default here and use that instead.
     // let materials = materials.expect("Failed to load MTL file");
 
+    let mut objects = Vec::new();
+    let mut emissive = Vec::new();
     for m in models.iter() {
         let mesh = &m.mesh;
-        let mat_id = mesh.material_id.unwrap();
-
-        let mat_file_name = String::from(rootfile) + materials[mat_id].diffuse_texture.as_str();
+        let material = &materials[mesh.material_id.unwrap()];
+
+        // tobj 不解析 Ke/map_Ke, 它们在 unknown_param 中
+        let emission_map = material
+            .unknown_param
+            .get("map_Ke")
+            .map(|file| load_texture(&(String::from(rootfile) + file)));
+        let ke = match material.unknown_param.get("Ke") {
+            Some(ke) => parse_mtl_color(ke),
+            None if emission_map.is_some() => Color::new(1., 1., 1.),
+            None => Color::new(0., 0., 0.),
+        };
+        let is_emissive = emission_map.is_some() || ke.luminance() > 0.;
 
         // println!("{}", mat_file_name);
         // Todo: 这里重复的png/jpg可能会被多次load, 可以用一个Hash来优化一下
-        let tex = Arc::new(
-            image::open(mat_file_name)
-                .expect("load image failed")
-                .into_rgb8(),
-        );
+        let tex = if is_emissive {
+            None
+        } else {
+            Some(load_texture(
+                &(String::from(rootfile) + material.diffuse_texture.as_str()),
+            ))
+        };
 
-        assert!(!mesh.texcoords.is_empty());
+        assert!(!mesh.texcoords.is_empty() || (tex.is_none() && emission_map.is_none()));
 
         let mut vertices: Vec<Point3> = Vec::default(); // 存储所用到的点集
         for id in 0..mesh.positions.len() / 3 {
@@ -89,6 +174,13 @@ pub fn load_obj(
             let z = mesh.positions[3 * id + 2] as f64;
             vertices.push(Point3::new(x, y, z));
         }
+        let texcoord = |idx: usize, k: usize| {
+            if mesh.texcoords.is_empty() {
+                0.
+            } else {
+                mesh.texcoords[2 * idx + k] as f64
+            }
+        };
 
         let mut object = HittableList::default();
         for i in 0..mesh.indices.len() / 3 {
@@ -98,35 +190,47 @@ pub fn load_obj(
             let idx_y = mesh.indices[i * 3 + 1] as usize;
             let idx_z = mesh.indices[i * 3 + 2] as usize;
 
-            let u1 = mesh.texcoords[2 * idx_x] as f64;
-            let v1 = mesh.texcoords[2 * idx_x + 1] as f64;
-
-            let u2 = mesh.texcoords[2 * idx_y] as f64;
-            let v2 = mesh.texcoords[2 * idx_y + 1] as f64;
-
-            let u3 = mesh.texcoords[2 * idx_z] as f64;
-            let v3 = mesh.texcoords[2 * idx_z + 1] as f64;
-
-            let mat = ObjTexture::new(tex.clone(), u1, v1, u2, v2, u3, v3);
-            //let mut col = mat1.value(0.5, 0., &Point3::default()).unwrap();
-
-            let tri = Triangle::new(
-                rate * vertices[idx_x],
-                rate * vertices[idx_y],
-                rate * vertices[idx_z],
-                Lambertian::new_texture(mat),
-            );
-            object.add(Arc::new(tri));
+            let u1 = texcoord(idx_x, 0);
+            let v1 = texcoord(idx_x, 1);
+
+            let u2 = texcoord(idx_y, 0);
+            let v2 = texcoord(idx_y, 1);
+
+            let u3 = texcoord(idx_z, 0);
+            let v3 = texcoord(idx_z, 1);
+
+            if let Some(tex) = &tex {
+                let mat = ObjTexture::new(tex.clone(), u1, v1, u2, v2, u3, v3);
+                //let mut col = mat1.value(0.5, 0., &Point3::default()).unwrap();
+
+                let tri = Triangle::new(
+                    vertices[idx_x],
+                    vertices[idx_y],
+                    vertices[idx_z],
+                    Lambertian::new_texture(mat),
+                );
+                object.add(Arc::new(tri));
+            } else {
+                let map = emission_map
+                    .as_ref()
+                    .map(|map| ObjTexture::new(map.clone(), u1, v1, u2, v2, u3, v3));
+                let tri = Triangle::new(
+                    vertices[idx_x],
+                    vertices[idx_y],
+                    vertices[idx_z],
+                    DiffuseLight::new_texture(ObjEmission::new(ke, map)),
+                );
+                emissive.push(tri.clone());
+                object.add(Arc::new(tri));
+            }
             // println!("{}", i);
         }
 
         //std::process::exit(0);
         // println!("{}", object.objects.len());
-        let object = BvhNode::new_from_vec(object.objects, 0., 1.);
-        let object = Rotatey::new(object, rotate_angle);
-        let object = Translate::new(object, offset);
-        world.add(Arc::new(object));
+        objects.push(BvhNode::new_from_vec(object.objects, 0., 1.));
     }
+    (objects, emissive)
 }
 
 use raytracer_codegen::random_scene_macro;
@@ -139,11 +243,11 @@ pub fn cornell_box() -> (HittableList, HittableList) {
     let red = Lambertian::<SolidColor>::new(Color::new(0.65, 0.05, 0.05));
     let white = Lambertian::<SolidColor>::new(Color::new(0.73, 0.73, 0.73));
     let green = Lambertian::<SolidColor>::new(Color::new(0.12, 0.45, 0.15));
-    let light = DiffuseLight::<SolidColor>::new(Color::new(12., 12., 12.));
+    let light = DiffuseLight::<SolidColor>::new(Color::new(15., 15., 15.)).with_two_sided(false);
 
-    world.add(Arc::new(Rectangleyz::new(0., 555., 0., 555., 555., green)));
-    world.add(Arc::new(Rectangleyz::new(0., 555., 0., 555., 0., red)));
-    world.add(Arc::new(Rectanglexz::new(
+    world.add(Arc::new(rectangle::yz(0., 555., 0., 555., 555., green)));
+    world.add(Arc::new(rectangle::yz(0., 555., 0., 555., 0., red)));
+    world.add(Arc::new(rectangle::xz(
         0.,
         555.,
         0.,
@@ -151,7 +255,7 @@ pub fn cornell_box() -> (HittableList, HittableList) {
         555.,
         white.clone(),
     )));
-    world.add(Arc::new(Rectanglexz::new(
+    world.add(Arc::new(rectangle::xz(
         0.,
         555.,
         0.,
@@ -159,43 +263,17 @@ pub fn cornell_box() -> (HittableList, HittableList) {
         0.,
         white.clone(),
     )));
-    world.add(Arc::new(Rectanglexy::new(0., 555., 0., 555., 555., white)));
-
-    let light1 = Arc::new(
-        //    Translate::new(
-        Rectangleyz::new(213., 343., 227., 332., 554., light.clone()),
-        //     Point3::new(0., 100., 0.,)
-        // ),
-    );
-    world.add(light1.clone());
-    lights.add(light1);
-
-    let light2 = Arc::new(
-        //    Translate::new(
-        Rectangleyz::new(213., 343., 227., 332., 1., light.clone()),
-        //     Point3::new(0., 100., 0.,)
-        // ),
-    );
-    world.add(light2.clone());
-    lights.add(light2);
-
-    let light3 = Arc::new(
-        //    Translate::new(
-        Rectanglexz::new(213., 343., 227., 332., 554., light.clone()),
-        //     Point3::new(0., 100., 0.,)
-        // ),
-    );
-    world.add(light3.clone());
-    lights.add(light3);
-
-    let light4 = Arc::new(
-        //    Translate::new(
-        Rectanglexz::new(213., 343., 227., 332., 1., light),
-        //     Point3::new(0., 100., 0.,)
-        // ),
-    );
-    world.add(light4.clone());
-    lights.add(light4);
+    world.add(Arc::new(rectangle::xy(0., 555., 0., 555., 555., white)));
+
+    // 顶灯只向下发光: 法向 u x v = -y 朝向箱内
+    let light = Arc::new(Quad::new(
+        Point3::new(213., 554., 227.),
+        Vec3::new(130., 0., 0.),
+        Vec3::new(0., 0., 105.),
+        light,
+    ));
+    world.add(light.clone());
+    lights.add(light);
 
     // let aluminum = Metal::new(Color::new(0.8, 0.85, 0.88), 0.);
     // let box1 = Cube::new(
@@ -234,12 +312,72 @@ pub fn cornell_box() -> (HittableList, HittableList) {
 
     load_obj(
         &mut world,
+        &mut lights,
         "obj_material/",
-        200.,
         "patrick.obj",
-        Vec3::new(270., 70., 450.),
-        180.,
+        Transform::translate(Vec3::new(270., 70., 450.))
+            * Transform::rotate_y(180.)
+            * Transform::scale(200., 200., 200.),
+    );
+
+    (world, lights)
+}
+
+// 运动模糊: 关键帧动画的立方体与obj模型, 快门区间取 [0, 1]
+pub fn motion_blur() -> (HittableList, HittableList) {
+    let mut world = HittableList::default();
+    let mut lights = HittableList::default();
+
+    let ground = Lambertian::<SolidColor>::new(Color::new(0.48, 0.83, 0.53));
+    let red = Lambertian::<SolidColor>::new(Color::new(0.65, 0.05, 0.05));
+    let light = DiffuseLight::<SolidColor>::new(Color::new(7., 7., 7.));
+
+    world.add(Arc::new(rectangle::xz(
+        -1000., 1000., -1000., 1000., 0., ground,
+    )));
+    let light = Arc::new(rectangle::xz(123., 423., 147., 412., 554., light));
+    world.add(light.clone());
+    lights.add(light);
+
+    // 边平移边绕y轴旋转的立方体
+    let cube = Cube::new(
+        Point3::new(-50., 0., -50.),
+        Point3::new(50., 100., 50.),
+        red,
+    );
+    let animation = AnimatedTransform::new(
+        vec![
+            Keyframe::at(0., Vec3::new(150., 0., 300.)),
+            Keyframe::new(
+                1.,
+                Vec3::new(250., 0., 300.),
+                Quaternion::from_axis_angle(Vec3::new(0., 1., 0.), 45.),
+                Vec3::new(1., 1., 1.),
+            ),
+        ],
+        Interpolation::Linear,
+    );
+    world.add(Arc::new(Animated::new(cube, animation)));
+
+    // 放大并旋转落下的obj模型
+    let animation = AnimatedTransform::new(
+        vec![
+            Keyframe::new(
+                0.,
+                Vec3::new(400., 150., 400.),
+                Quaternion::from_axis_angle(Vec3::new(0., 1., 0.), 180.),
+                Vec3::new(200., 200., 200.),
+            ),
+            Keyframe::new(
+                1.,
+                Vec3::new(400., 70., 400.),
+                Quaternion::from_axis_angle(Vec3::new(0., 1., 0.), 150.),
+                Vec3::new(200., 200., 200.),
+            ),
+        ],
+        Interpolation::Smooth,
     );
+    load_obj_animated(&mut world, "obj_material/", "patrick.obj", animation);
 
     (world, lights)
 }
@@ -274,7 +412,7 @@ pub fn final_scene() -> Self {
     world.add(Arc::new(BvhNode::new(boxes1, 0., 1.)));
 
     let light = Arc::new(DiffuseLight::new(Color::new(7., 7., 7.)));
-    world.add(Arc::new(Rectanglexz::new(
+    world.add(Arc::new(rectangle::xz(
         123., 423., 147., 412., 554., light,
     )));
 
@@ -371,14 +509,14 @@ pub fn cornell_box_smoke() -> Self {
 
     world
         .objects
-        .push(Arc::new(Rectangleyz::new(0., 555., 0., 555., 555., green)));
+        .push(Arc::new(rectangle::yz(0., 555., 0., 555., 555., green)));
     world
         .objects
-        .push(Arc::new(Rectangleyz::new(0., 555., 0., 555., 0., red)));
-    world.add(Arc::new(Rectanglexz::new(
+        .push(Arc::new(rectangle::yz(0., 555., 0., 555., 0., red)));
+    world.add(Arc::new(rectangle::xz(
         113., 443., 127., 432., 554., light,
     )));
-    world.add(Arc::new(Rectanglexz::new(
+    world.add(Arc::new(rectangle::xz(
         0.,
         555.,
         0.,
@@ -386,7 +524,7 @@ pub fn cornell_box_smoke() -> Self {
         0.,
         white.clone(),
     )));
-    world.add(Arc::new(Rectanglexz::new(
+    world.add(Arc::new(rectangle::xz(
         0.,
         555.,
         0.,
@@ -394,7 +532,7 @@ pub fn cornell_box_smoke() -> Self {
         555.,
         white.clone(),
     )));
-    world.add(Arc::new(Rectanglexy::new(
+    world.add(Arc::new(rectangle::xy(
         0.,
         555.,
         0.,
@@ -452,7 +590,7 @@ pub fn simple_light() -> Self {
     let difflight = Arc::new(DiffuseLight::new(Color::new(4., 4., 4.)));
     world
         .objects
-        .push(Arc::new(Rectanglexy::new(3., 5., 1., 3., -2., difflight)));
+        .push(Arc::new(rectangle::xy(3., 5., 1., 3., -2., difflight)));
     world
 }
 pub fn load_image() -> Self {
@@ -0,0 +1,44 @@
+use super::SDF;
+use crate::basic::VEC3::Point3;
+
+// Mandelbulb 分形的距离估计, 大致位于半径 1.2 的球内
+pub struct Mandelbulb {
+    pub power: f64,
+    pub iterations: usize,
+}
+
+impl Mandelbulb {
+    pub fn new(power: f64, iterations: usize) -> Self {
+        Self { power, iterations }
+    }
+}
+
+impl SDF for Mandelbulb {
+    fn distance(&self, p: &Point3) -> f64 {
+        let mut z = *p;
+        let mut dr = 1.;
+        let mut r = z.len();
+
+        for _ in 0..self.iterations {
+            if r > 2. || r == 0. {
+                break;
+            }
+            // 球坐标下 z -> z^power + p
+            let theta = (z.z() / r).acos() * self.power;
+            let phi = z.y().atan2(z.x()) * self.power;
+            dr = r.powf(self.power - 1.) * self.power * dr + 1.;
+            let zr = r.powf(self.power);
+            z = Point3::new(
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            ) * zr
+                + *p;
+            r = z.len();
+        }
+        if r == 0. {
+            return 0.;
+        }
+        0.5 * r.ln() * r / dr
+    }
+}
@@ -0,0 +1,23 @@
+pub mod fractal;
+pub mod ops;
+pub mod shapes;
+
+use crate::basic::VEC3::{Point3, Vec3};
+
+// 有向距离场: 外部为正, 内部为负, 绝对值不超过到表面的距离
+pub trait SDF: Send + Sync {
+    fn distance(&self, p: &Point3) -> f64;
+
+    // 四面体差分估计梯度, 即表面法向
+    fn normal(&self, p: &Point3, h: f64) -> Vec3 {
+        let k0 = Vec3::new(1., -1., -1.);
+        let k1 = Vec3::new(-1., -1., 1.);
+        let k2 = Vec3::new(-1., 1., -1.);
+        let k3 = Vec3::new(1., 1., 1.);
+        let n = k0 * self.distance(&(*p + k0 * h))
+            + k1 * self.distance(&(*p + k1 * h))
+            + k2 * self.distance(&(*p + k2 * h))
+            + k3 * self.distance(&(*p + k3 * h));
+        n.unit_vector()
+    }
+}
@@ -0,0 +1,132 @@
+// 距离场的组合: 布尔运算(可平滑), 平移, 重复, 扭转
+use super::SDF;
+use crate::basic::VEC3::{Point3, Vec3};
+
+// 多项式平滑最小值, k 为过渡区宽度, k = 0 时为普通的 min
+fn smooth_min(a: f64, b: f64, k: f64) -> f64 {
+    if k <= 0. {
+        return a.min(b);
+    }
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0., 1.);
+    b + (a - b) * h - k * h * (1. - h)
+}
+
+pub struct Union<A: SDF, B: SDF> {
+    pub a: A,
+    pub b: B,
+    pub k: f64,
+}
+
+impl<A: SDF, B: SDF> Union<A, B> {
+    pub fn new(a: A, b: B, k: f64) -> Self {
+        Self { a, b, k }
+    }
+}
+
+impl<A: SDF, B: SDF> SDF for Union<A, B> {
+    fn distance(&self, p: &Point3) -> f64 {
+        smooth_min(self.a.distance(p), self.b.distance(p), self.k)
+    }
+}
+
+pub struct Intersection<A: SDF, B: SDF> {
+    pub a: A,
+    pub b: B,
+    pub k: f64,
+}
+
+impl<A: SDF, B: SDF> Intersection<A, B> {
+    pub fn new(a: A, b: B, k: f64) -> Self {
+        Self { a, b, k }
+    }
+}
+
+impl<A: SDF, B: SDF> SDF for Intersection<A, B> {
+    fn distance(&self, p: &Point3) -> f64 {
+        -smooth_min(-self.a.distance(p), -self.b.distance(p), self.k)
+    }
+}
+
+// a 减去 b
+pub struct Subtraction<A: SDF, B: SDF> {
+    pub a: A,
+    pub b: B,
+    pub k: f64,
+}
+
+impl<A: SDF, B: SDF> Subtraction<A, B> {
+    pub fn new(a: A, b: B, k: f64) -> Self {
+        Self { a, b, k }
+    }
+}
+
+impl<A: SDF, B: SDF> SDF for Subtraction<A, B> {
+    fn distance(&self, p: &Point3) -> f64 {
+        -smooth_min(-self.a.distance(p), self.b.distance(p), self.k)
+    }
+}
+
+pub struct Translate<S: SDF> {
+    pub sdf: S,
+    pub offset: Vec3,
+}
+
+impl<S: SDF> Translate<S> {
+    pub fn new(sdf: S, offset: Vec3) -> Self {
+        Self { sdf, offset }
+    }
+}
+
+impl<S: SDF> SDF for Translate<S> {
+    fn distance(&self, p: &Point3) -> f64 {
+        self.sdf.distance(&(*p - self.offset))
+    }
+}
+
+// 以 period 为周期无限重复, 某一分量为 0 表示该方向不重复
+// 形状需小于半个周期, 否则距离会偏大
+pub struct Repeat<S: SDF> {
+    pub sdf: S,
+    pub period: Vec3,
+}
+
+impl<S: SDF> Repeat<S> {
+    pub fn new(sdf: S, period: Vec3) -> Self {
+        Self { sdf, period }
+    }
+}
+
+impl<S: SDF> SDF for Repeat<S> {
+    fn distance(&self, p: &Point3) -> f64 {
+        let mut q = *p;
+        for a in 0..3 {
+            let c = self.period[a];
+            if c > 0. {
+                q[a] = p[a] - c * (p[a] / c).round();
+            }
+        }
+        self.sdf.distance(&q)
+    }
+}
+
+// 绕 y 轴扭转, 每单位高度旋转 rate 弧度
+pub struct Twist<S: SDF> {
+    pub sdf: S,
+    pub rate: f64,
+}
+
+impl<S: SDF> Twist<S> {
+    pub fn new(sdf: S, rate: f64) -> Self {
+        Self { sdf, rate }
+    }
+}
+
+impl<S: SDF> SDF for Twist<S> {
+    fn distance(&self, p: &Point3) -> f64 {
+        let (sin, cos) = (self.rate * p.y()).sin_cos();
+        let q = Point3::new(cos * p.x() - sin * p.z(), p.y(), sin * p.x() + cos * p.z());
+        // 扭转后不再是精确距离, 按该点处的拉伸程度缩小步长
+        let rho = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        self.sdf.distance(&q) / (1. + (self.rate * rho).powi(2)).sqrt()
+    }
+}
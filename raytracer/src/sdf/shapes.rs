@@ -0,0 +1,109 @@
+// 以原点为中心的基本形状, 平移旋转用 ops 里的组合子
+use super::SDF;
+use crate::basic::VEC3::{Point3, Vec3};
+
+pub struct SdfSphere {
+    pub radius: f64,
+}
+
+impl SdfSphere {
+    pub fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl SDF for SdfSphere {
+    fn distance(&self, p: &Point3) -> f64 {
+        p.len() - self.radius
+    }
+}
+
+// 长方体, half 为三个方向的半边长
+pub struct SdfBox {
+    pub half: Vec3,
+}
+
+impl SdfBox {
+    pub fn new(half: Vec3) -> Self {
+        Self { half }
+    }
+}
+
+impl SDF for SdfBox {
+    fn distance(&self, p: &Point3) -> f64 {
+        let q = Vec3::new(
+            p.x().abs() - self.half.x(),
+            p.y().abs() - self.half.y(),
+            p.z().abs() - self.half.z(),
+        );
+        let outside = Vec3::new(q.x().max(0.), q.y().max(0.), q.z().max(0.)).len();
+        let inside = q.x().max(q.y()).max(q.z()).min(0.);
+        outside + inside
+    }
+}
+
+// 圆角长方体: 总体大小仍为 half, 棱角处圆角半径 radius
+pub struct SdfRoundBox {
+    pub inner: SdfBox,
+    pub radius: f64,
+}
+
+impl SdfRoundBox {
+    pub fn new(half: Vec3, radius: f64) -> Self {
+        let r = Vec3::new(radius, radius, radius);
+        Self {
+            inner: SdfBox::new(half - r),
+            radius,
+        }
+    }
+}
+
+impl SDF for SdfRoundBox {
+    fn distance(&self, p: &Point3) -> f64 {
+        self.inner.distance(p) - self.radius
+    }
+}
+
+// 圆环, 位于 xz 平面
+pub struct SdfTorus {
+    pub major_radius: f64,
+    pub minor_radius: f64,
+}
+
+impl SdfTorus {
+    pub fn new(major_radius: f64, minor_radius: f64) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl SDF for SdfTorus {
+    fn distance(&self, p: &Point3) -> f64 {
+        let rho = (p.x() * p.x() + p.z() * p.z()).sqrt() - self.major_radius;
+        (rho * rho + p.y() * p.y()).sqrt() - self.minor_radius
+    }
+}
+
+// 胶囊体: 线段 a -> b 周围半径 radius
+pub struct SdfCapsule {
+    pub a: Point3,
+    pub b: Point3,
+    pub radius: f64,
+}
+
+impl SdfCapsule {
+    pub fn new(a: Point3, b: Point3, radius: f64) -> Self {
+        Self { a, b, radius }
+    }
+}
+
+impl SDF for SdfCapsule {
+    fn distance(&self, p: &Point3) -> f64 {
+        let pa = *p - self.a;
+        let ba = self.b - self.a;
+        let h = (Vec3::dot(&pa, &ba) / ba.len_square()).clamp(0., 1.);
+        (pa - ba * h).len() - self.radius
+    }
+}
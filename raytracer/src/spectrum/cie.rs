@@ -0,0 +1,89 @@
+use crate::basic::VEC3::{Color, Vec3};
+
+// 光谱模式覆盖的波长范围(nm)
+pub const LAMBDA_MIN: f64 = 360.;
+pub const LAMBDA_MAX: f64 = 830.;
+
+// CIE 1931 标准观察者的分段高斯拟合
+// reference: Wyman, Sloan, Shirley. Simple Analytic Approximations to the CIE XYZ Color Matching Functions. 2013
+fn lobe(lambda: f64, mu: f64, sigma1: f64, sigma2: f64) -> f64 {
+    let t = (lambda - mu) / if lambda < mu { sigma1 } else { sigma2 };
+    (-0.5 * t * t).exp()
+}
+
+pub fn x_bar(lambda: f64) -> f64 {
+    1.056 * lobe(lambda, 599.8, 37.9, 31.0) + 0.362 * lobe(lambda, 442.0, 16.0, 26.7)
+        - 0.065 * lobe(lambda, 501.1, 20.4, 26.2)
+}
+pub fn y_bar(lambda: f64) -> f64 {
+    0.821 * lobe(lambda, 568.8, 46.9, 40.5) + 0.286 * lobe(lambda, 530.9, 16.3, 31.1)
+}
+pub fn z_bar(lambda: f64) -> f64 {
+    1.217 * lobe(lambda, 437.0, 11.8, 36.0) + 0.681 * lobe(lambda, 459.0, 26.0, 13.8)
+}
+pub fn cmf(lambda: f64) -> Vec3 {
+    Vec3::new(x_bar(lambda), y_bar(lambda), z_bar(lambda))
+}
+
+// CIE 标准光源 D65, 360nm ~ 830nm 每 10nm 一个值
+const D65: [f64; 48] = [
+    46.6383, 52.0891, 49.9755, 54.6482, 82.7549, 91.486, 93.4318, 86.6823, 104.865, 117.008,
+    117.812, 114.861, 115.923, 108.811, 109.354, 107.802, 104.79, 107.689, 104.405, 104.046, 100.,
+    96.3342, 95.788, 88.6856, 90.0062, 89.5991, 87.6987, 83.2886, 83.6992, 80.0268, 80.2146,
+    82.2778, 78.2842, 69.7213, 71.6091, 74.349, 61.604, 69.8856, 75.087, 63.5927, 46.4182, 66.8054,
+    63.3828, 64.304, 59.4519, 51.959, 57.4406, 60.3125,
+];
+
+pub fn d65(lambda: f64) -> f64 {
+    if !(LAMBDA_MIN..=LAMBDA_MAX).contains(&lambda) {
+        return 0.;
+    }
+    let x = (lambda - LAMBDA_MIN) / 10.;
+    let i = (x as usize).min(D65.len() - 2);
+    let t = x - i as f64;
+    D65[i] * (1. - t) + D65[i + 1] * t
+}
+
+// 以 1nm 为步长在可见光范围内积分
+pub fn integrate<F: Fn(f64) -> f64>(f: F) -> f64 {
+    let mut sum = 0.;
+    let mut lambda = LAMBDA_MIN;
+    while lambda <= LAMBDA_MAX {
+        sum += f(lambda);
+        lambda += 1.;
+    }
+    sum
+}
+
+// 光谱对应的 XYZ, 归一化使得 Y 与 ∫ȳ 之比即为亮度
+pub fn spectrum_to_xyz<F: Fn(f64) -> f64>(f: F) -> Vec3 {
+    let y_integral = integrate(y_bar);
+    Vec3::new(
+        integrate(|l| f(l) * x_bar(l)),
+        integrate(|l| f(l) * y_bar(l)),
+        integrate(|l| f(l) * z_bar(l)),
+    ) / y_integral
+}
+
+// 反射率光谱在 D65 照明下的颜色, 以 10nm 为步长积分
+pub fn reflectance_to_srgb<F: Fn(f64) -> f64>(f: F) -> Color {
+    let mut xyz = Vec3::default();
+    let mut white = 0.;
+    let mut lambda = LAMBDA_MIN;
+    while lambda <= LAMBDA_MAX {
+        let illuminant = d65(lambda);
+        xyz += cmf(lambda) * (f(lambda) * illuminant);
+        white += y_bar(lambda) * illuminant;
+        lambda += 10.;
+    }
+    xyz_to_srgb(xyz / white)
+}
+
+// XYZ -> 线性 sRGB (D65 白点)
+pub fn xyz_to_srgb(xyz: Vec3) -> Color {
+    Color::new(
+        3.2404542 * xyz.x - 1.5371385 * xyz.y - 0.4985314 * xyz.z,
+        -0.969266 * xyz.x + 1.8760108 * xyz.y + 0.041556 * xyz.z,
+        0.0556434 * xyz.x - 0.2040259 * xyz.y + 1.0572252 * xyz.z,
+    )
+}
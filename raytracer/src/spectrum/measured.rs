@@ -0,0 +1,139 @@
+use std::fs;
+
+use super::Spectrum;
+
+// 测量得到的光谱(如光源的光谱功率分布): 按波长分段线性插值, 范围以外为0
+#[derive(Clone)]
+pub struct PiecewiseLinear {
+    lambda: Vec<f64>,
+    value: Vec<f64>,
+}
+
+impl PiecewiseLinear {
+    // samples: (波长nm, 值)
+    pub fn new(mut samples: Vec<(f64, f64)>) -> Self {
+        assert!(!samples.is_empty(), "spectrum has no samples");
+        samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        Self {
+            lambda: samples.iter().map(|s| s.0).collect(),
+            value: samples.iter().map(|s| s.1).collect(),
+        }
+    }
+
+    // 每行一个 "波长 值", 以空白或逗号分隔, # 开头的行为注释
+    pub fn from_file(filename: &str) -> Self {
+        let text = fs::read_to_string(filename).expect("failed to read spectrum file");
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let samples = text
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let numbers: Vec<f64> = line
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.parse().expect("invalid number in spectrum file"))
+                    .collect();
+                assert!(
+                    numbers.len() >= 2,
+                    "spectrum line needs a wavelength and a value"
+                );
+                (numbers[0], numbers[1])
+            })
+            .collect();
+        Self::new(samples)
+    }
+}
+
+impl Spectrum for PiecewiseLinear {
+    fn value(&self, lambda: f64) -> f64 {
+        let last = self.lambda.len() - 1;
+        if lambda < self.lambda[0] || lambda > self.lambda[last] {
+            return 0.;
+        }
+        let i = self.lambda.partition_point(|&l| l <= lambda) - 1;
+        if i == last {
+            return self.value[last];
+        }
+        let t = (lambda - self.lambda[i]) / (self.lambda[i + 1] - self.lambda[i]);
+        self.value[i] * (1. - t) + self.value[i + 1] * t
+    }
+}
+
+// 黑体辐射, 归一化使得峰值为1
+#[derive(Clone, Copy)]
+pub struct Blackbody {
+    temperature: f64, // 开尔文
+    normalization: f64,
+}
+
+impl Blackbody {
+    pub fn new(temperature: f64) -> Self {
+        // Wien 位移定律求峰值波长
+        let peak = 2.8977721e-3 / temperature * 1e9;
+        Self {
+            temperature,
+            normalization: 1. / Self::planck(peak, temperature),
+        }
+    }
+
+    fn planck(lambda: f64, temperature: f64) -> f64 {
+        const C: f64 = 299792458.;
+        const H: f64 = 6.62606957e-34;
+        const KB: f64 = 1.3806488e-23;
+        let l = lambda * 1e-9;
+        2. * H * C * C / (l.powi(5) * ((H * C / (l * KB * temperature)).exp() - 1.))
+    }
+}
+
+impl Spectrum for Blackbody {
+    fn value(&self, lambda: f64) -> f64 {
+        Self::planck(lambda, self.temperature) * self.normalization
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolation() {
+        // 乱序输入也应按波长排序
+        let s = PiecewiseLinear::new(vec![(500., 1.), (400., 0.), (700., 0.5)]);
+        assert_eq!(s.value(400.), 0.);
+        assert_eq!(s.value(500.), 1.);
+        assert_eq!(s.value(700.), 0.5);
+        assert!((s.value(450.) - 0.5).abs() < 1e-12);
+        assert!((s.value(650.) - 0.625).abs() < 1e-12);
+    }
+
+    #[test]
+    fn out_of_range_is_zero() {
+        let s = PiecewiseLinear::new(vec![(400., 2.), (700., 3.)]);
+        assert_eq!(s.value(399.9), 0.);
+        assert_eq!(s.value(700.1), 0.);
+        // 只有一个采样点时只在该波长处有值
+        let single = PiecewiseLinear::new(vec![(550., 4.)]);
+        assert_eq!(single.value(550.), 4.);
+        assert_eq!(single.value(551.), 0.);
+    }
+
+    #[test]
+    fn parse() {
+        let s = PiecewiseLinear::parse("# lambda value\n\n400, 1.0\n  500\t3 0.1\n");
+        assert_eq!(s.lambda, vec![400., 500.]);
+        assert_eq!(s.value, vec![1., 3.]);
+        assert!((s.value(425.) - 1.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn blackbody_peak_is_one() {
+        let b = Blackbody::new(5000.);
+        let peak = 2.8977721e-3 / 5000. * 1e9;
+        assert!((b.value(peak) - 1.).abs() < 1e-9);
+        assert!(b.value(peak - 50.) < 1. && b.value(peak + 50.) < 1.);
+    }
+}
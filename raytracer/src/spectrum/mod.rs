@@ -0,0 +1,220 @@
+pub mod cie;
+pub mod measured;
+pub mod rgb;
+
+use crate::{
+    basic::{
+        RAY::Ray,
+        VEC3::{Color, Vec3},
+    },
+    material::{Material, ScatterRecord},
+    Hit::HitRecord,
+};
+use rgb::RgbToSpectrum;
+
+// 随波长变化的量, 波长以nm为单位
+pub trait Spectrum: Send + Sync {
+    fn value(&self, lambda: f64) -> f64;
+}
+
+impl<F: Fn(f64) -> f64 + Send + Sync> Spectrum for F {
+    fn value(&self, lambda: f64) -> f64 {
+        self(lambda)
+    }
+}
+
+// 每条路径携带的三个波长, 与 Color 的三个分量一一对应; 第一个为 hero 波长
+#[derive(Clone, Copy)]
+pub struct SampledWavelengths {
+    lambda: [f64; 3],
+    pdf: [f64; 3],
+    secondary_terminated: bool,
+}
+
+impl SampledWavelengths {
+    // 按人眼敏感程度重要性采样, 三个波长在 u 上等距分层
+    // reference: Radziszewski et al. An Improved Technique for Full Spectral Rendering. 2009
+    pub fn sample_visible(u: f64) -> Self {
+        let mut lambda = [0.; 3];
+        let mut pdf = [0.; 3];
+        for i in 0..3 {
+            let ui = (u + i as f64 / 3.).fract();
+            lambda[i] = 538. - 138.888889 * (0.85691062 - 1.82750197 * ui).atanh();
+            pdf[i] = if (cie::LAMBDA_MIN..=cie::LAMBDA_MAX).contains(&lambda[i]) {
+                0.0039398042 / (0.0072 * (lambda[i] - 538.)).cosh().powi(2)
+            } else {
+                0.
+            };
+        }
+        Self {
+            lambda,
+            pdf,
+            secondary_terminated: false,
+        }
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.lambda[0]
+    }
+
+    // 色散后三个波长的光路不再相同, 只保留 hero 波长; 已经终止过时返回None
+    pub fn terminate_secondary(&self) -> Option<Self> {
+        if self.secondary_terminated {
+            return None;
+        }
+        Some(Self {
+            secondary_terminated: true,
+            ..*self
+        })
+    }
+    // 终止后的光路颜色: 只剩 hero 波长, 承担全部三个样本的权重
+    pub fn hero_only(color: Color) -> Color {
+        Color::new(3. * color.x, 0., 0.)
+    }
+
+    pub fn sample(&self, spectrum: &dyn Spectrum) -> Color {
+        Color::new(
+            spectrum.value(self.lambda[0]),
+            spectrum.value(self.lambda[1]),
+            spectrum.value(self.lambda[2]),
+        )
+    }
+
+    // 路径在各波长处的辐射亮度 -> XYZ 的蒙特卡洛估计
+    pub fn to_xyz(&self, l: Color, y_integral: f64) -> Vec3 {
+        let mut xyz = Vec3::default();
+        for i in 0..3 {
+            if self.pdf[i] > 0. {
+                xyz += cie::cmf(self.lambda[i]) * (l[i as u32] / self.pdf[i]);
+            }
+        }
+        xyz / (3. * y_integral)
+    }
+}
+
+// 一条光谱路径所需的转换表与波长
+#[derive(Clone, Copy)]
+pub struct Spectral<'a> {
+    pub table: &'a RgbToSpectrum,
+    pub lambda: SampledWavelengths,
+}
+
+impl<'a> Spectral<'a> {
+    pub fn albedo(&self, rgb: Color) -> Color {
+        self.lambda.sample(&self.table.albedo(rgb))
+    }
+    pub fn unbounded(&self, rgb: Color) -> Color {
+        self.table.unbounded(rgb, &self.lambda)
+    }
+    pub fn illuminant(&self, rgb: Color) -> Color {
+        self.table.illuminant(rgb, &self.lambda)
+    }
+    pub fn sample(&self, spectrum: &dyn Spectrum) -> Color {
+        self.lambda.sample(spectrum)
+    }
+}
+
+// 积分器中的颜色: RGB 模式下即为 RGB, 光谱模式下为各采样波长处的值
+// 材质与光源仍给出 RGB, 在进入光路时转换为光谱
+#[derive(Clone, Copy)]
+pub enum ColorMode<'a> {
+    Rgb,
+    Spectral(Spectral<'a>),
+}
+
+impl<'a> ColorMode<'a> {
+    pub fn new(table: Option<&'a RgbToSpectrum>, u: f64) -> Self {
+        match table {
+            Some(table) => ColorMode::Spectral(Spectral {
+                table,
+                lambda: SampledWavelengths::sample_visible(u),
+            }),
+            None => ColorMode::Rgb,
+        }
+    }
+
+    // 反射率, 衰减等 [0, 1] 内的量
+    pub fn albedo(&self, rgb: Color) -> Color {
+        match self {
+            ColorMode::Rgb => rgb,
+            ColorMode::Spectral(s) => s.albedo(rgb),
+        }
+    }
+    // 吸收系数等没有上界的量
+    pub fn unbounded(&self, rgb: Color) -> Color {
+        match self {
+            ColorMode::Rgb => rgb,
+            ColorMode::Spectral(s) => s.unbounded(rgb),
+        }
+    }
+    // 随波长变化的反射率(如薄膜干涉), RGB 模式下取其在白光下的颜色
+    pub fn reflectance(&self, f: &dyn Spectrum) -> Color {
+        match self {
+            // 超出 sRGB 色域的分量截断
+            ColorMode::Rgb => {
+                let rgb = cie::reflectance_to_srgb(|lambda| f.value(lambda));
+                Color::new(
+                    rgb.x.clamp(0., 1.),
+                    rgb.y.clamp(0., 1.),
+                    rgb.z.clamp(0., 1.),
+                )
+            }
+            ColorMode::Spectral(s) => s.sample(f),
+        }
+    }
+    // 光源与背景的辐射亮度
+    pub fn illuminant(&self, rgb: Color) -> Color {
+        match self {
+            ColorMode::Rgb => rgb,
+            ColorMode::Spectral(s) => s.illuminant(rgb),
+        }
+    }
+    // 材质的散射, attenuation 为当前模式下的颜色
+    pub fn scatter(
+        &self,
+        mat: &dyn Material,
+        r_in: &Ray,
+        rec: &HitRecord,
+    ) -> Option<ScatterRecord> {
+        match self {
+            ColorMode::Rgb => mat.scatter(r_in, rec),
+            ColorMode::Spectral(s) => mat.scatter_spectral(r_in, rec, s),
+        }
+    }
+    pub fn emitted(&self, mat: &dyn Material, r_in: &Ray, rec: &HitRecord) -> Option<Color> {
+        match self {
+            ColorMode::Rgb => mat.emitted_from(r_in, rec),
+            ColorMode::Spectral(s) => mat.emitted_spectral(r_in, rec, s),
+        }
+    }
+
+    // 与波长有关的折射率等使用的波长, RGB 模式下为None
+    pub fn wavelength(&self) -> Option<f64> {
+        match self {
+            ColorMode::Rgb => None,
+            ColorMode::Spectral(s) => Some(s.lambda.hero()),
+        }
+    }
+    // 发生色散时, 之后的光路只追踪 hero 波长; 返回新的模式, 不需要终止时返回None
+    pub fn terminate_secondary(&self) -> Option<Self> {
+        match self {
+            ColorMode::Rgb => None,
+            ColorMode::Spectral(s) => s.lambda.terminate_secondary().map(|lambda| {
+                ColorMode::Spectral(Spectral {
+                    table: s.table,
+                    lambda,
+                })
+            }),
+        }
+    }
+
+    // 路径的颜色 -> 胶片上的线性 sRGB
+    pub fn to_rgb(&self, color: Color) -> Color {
+        match self {
+            ColorMode::Rgb => color,
+            ColorMode::Spectral(s) => {
+                cie::xyz_to_srgb(s.lambda.to_xyz(color, s.table.y_integral()))
+            }
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    convert::TryInto,
+    fs,
+    io::{self, Write},
+};
+
+use super::{
+    cie::{cmf, d65, integrate, xyz_to_srgb, y_bar, LAMBDA_MAX, LAMBDA_MIN},
+    SampledWavelengths, Spectrum,
+};
+use crate::basic::VEC3::Color;
+
+// RGB 到光谱的转换表: 每个分量最大的通道各一张 RES^3 的表
+// reference: Jakob, Hanika. A Low-Dimensional Function Space for Efficient Spectral Upsampling. 2019
+const RES: usize = 32;
+// 拟合时的波长采样间隔(nm)
+const FIT_STEP: f64 = 5.;
+// 缓存文件: 文件头(标识, RES, FIT_STEP), 随后为全部系数(f64, 小端序)
+const CACHE_MAGIC: &[u8; 8] = b"RGB2SPEC";
+
+fn sigmoid(x: f64) -> f64 {
+    if x.is_infinite() {
+        return if x > 0. { 1. } else { 0. };
+    }
+    0.5 + x / (2. * (1. + x * x).sqrt())
+}
+
+fn smoothstep(x: f64) -> f64 {
+    x * x * (3. - 2. * x)
+}
+
+// s(λ) = sigmoid(c0 t^2 + c1 t + c2), t 为归一化到 [0, 1] 的波长, 取值总在 [0, 1] 内
+#[derive(Clone, Copy)]
+pub struct RgbSigmoid {
+    c: [f64; 3],
+}
+
+impl Spectrum for RgbSigmoid {
+    fn value(&self, lambda: f64) -> f64 {
+        let t = (lambda - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN);
+        sigmoid((self.c[0] * t + self.c[1]) * t + self.c[2])
+    }
+}
+
+pub struct RgbToSpectrum {
+    scale: Vec<f64>,       // 表的第三维: 最大分量的取值, 在0和1附近更密
+    coeffs: Vec<[f64; 3]>, // 下标 [最大通道][z][y][x]
+    y_integral: f64,       // ∫ȳ, 把光谱积分为 XYZ 时的归一化
+    d65_norm: f64,         // 使得发光 D65 / d65_norm 的亮度 Y = 1
+}
+
+impl RgbToSpectrum {
+    pub fn new() -> Self {
+        Self::fit(|| {})
+    }
+
+    // 用 Gauss-Newton 逐格拟合, 相邻格子以上一次的结果为初值
+    // 拟合完表中的每一列(固定最大通道与 x, y)后调用一次 progress, 共 columns() 次
+    pub fn fit<F: FnMut()>(mut progress: F) -> Self {
+        let d65_y = integrate(|l| d65(l) * y_bar(l));
+
+        // 反射率光谱 s 在 D65 照明下的 sRGB = Σ s(λ_k) * weights[k]
+        let mut weights = Vec::new();
+        let mut lambda = LAMBDA_MIN;
+        while lambda <= LAMBDA_MAX {
+            let t = (lambda - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN);
+            let w = xyz_to_srgb(cmf(lambda) * d65(lambda)) * FIT_STEP / d65_y;
+            weights.push((t, w));
+            lambda += FIT_STEP;
+        }
+
+        let scale = Self::scale();
+        let mut coeffs = vec![[0.; 3]; 3 * RES * RES * RES];
+        let start = RES / 5;
+        for l in 0..3 {
+            for j in 0..RES {
+                let y = j as f64 / (RES - 1) as f64;
+                for i in 0..RES {
+                    let x = i as f64 / (RES - 1) as f64;
+                    // 从 z = scale[start] 开始分别向两侧推进
+                    let ks: Vec<usize> = (start..RES).chain((0..start).rev()).collect();
+                    let mut c = [0.; 3];
+                    for &k in ks.iter() {
+                        if k + 1 == start {
+                            c = [0.; 3];
+                        }
+                        let z = scale[k];
+                        let mut rgb = Color::default();
+                        rgb[l as u32] = z;
+                        rgb[((l + 1) % 3) as u32] = x * z;
+                        rgb[((l + 2) % 3) as u32] = y * z;
+                        c = Self::fit_one(&weights, rgb, c);
+                        coeffs[((l * RES + k) * RES + j) * RES + i] = c;
+                    }
+                    progress();
+                }
+            }
+        }
+
+        Self::from_coeffs(scale, coeffs)
+    }
+
+    fn from_coeffs(scale: Vec<f64>, coeffs: Vec<[f64; 3]>) -> Self {
+        let y_integral = integrate(y_bar);
+        let d65_y = integrate(|l| d65(l) * y_bar(l));
+        Self {
+            scale,
+            coeffs,
+            y_integral,
+            d65_norm: d65_y / y_integral,
+        }
+    }
+    fn scale() -> Vec<f64> {
+        (0..RES)
+            .map(|k| smoothstep(smoothstep(k as f64 / (RES - 1) as f64)))
+            .collect()
+    }
+    pub fn columns() -> usize {
+        3 * RES * RES
+    }
+
+    fn cache_header() -> Vec<u8> {
+        let mut header = CACHE_MAGIC.to_vec();
+        header.extend_from_slice(&(RES as u32).to_le_bytes());
+        header.extend_from_slice(&FIT_STEP.to_le_bytes());
+        header
+    }
+    // 读取 save 写出的表; 文件不存在, 损坏或与当前的 RES/FIT_STEP 不符时返回None
+    pub fn load(path: &str) -> Option<Self> {
+        let bytes = fs::read(path).ok()?;
+        let header = Self::cache_header();
+        if bytes.len() != header.len() + 3 * RES * RES * RES * 3 * 8
+            || bytes[..header.len()] != header[..]
+        {
+            return None;
+        }
+        let values: Vec<f64> = bytes[header.len()..]
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        let coeffs = values.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+        Some(Self::from_coeffs(Self::scale(), coeffs))
+    }
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut bytes = Self::cache_header();
+        for c in self.coeffs.iter().flatten() {
+            bytes.extend_from_slice(&c.to_le_bytes());
+        }
+        fs::File::create(path)?.write_all(&bytes)
+    }
+
+    fn fit_one(weights: &[(f64, Color)], target: Color, mut c: [f64; 3]) -> [f64; 3] {
+        for _ in 0..30 {
+            let mut rgb = Color::default();
+            let mut jacobian = [Color::default(); 3];
+            for &(t, w) in weights.iter() {
+                let x = (c[0] * t + c[1]) * t + c[2];
+                let d = 0.5 / (1. + x * x).powf(1.5);
+                rgb += w * sigmoid(x);
+                jacobian[0] += w * (d * t * t);
+                jacobian[1] += w * (d * t);
+                jacobian[2] += w * d;
+            }
+            let r = rgb - target;
+            if r.len_square() < 1e-12 {
+                break;
+            }
+
+            // 克莱姆法则解 J * delta = r
+            let det = jacobian[0].dot(&jacobian[1].cross(jacobian[2]));
+            if det.abs() < 1e-15 {
+                break;
+            }
+            let delta = [
+                r.dot(&jacobian[1].cross(jacobian[2])) / det,
+                jacobian[0].dot(&r.cross(jacobian[2])) / det,
+                jacobian[0].dot(&jacobian[1].cross(r)) / det,
+            ];
+            for k in 0..3 {
+                c[k] -= delta[k];
+            }
+            // 纯色无法精确表示, 限制系数大小以免发散
+            let max = c.iter().fold(0., |m: f64, x| m.max(x.abs()));
+            if max > 200. {
+                for x in c.iter_mut() {
+                    *x *= 200. / max;
+                }
+            }
+        }
+        c
+    }
+
+    // [0, 1] 内的反射率
+    pub fn albedo(&self, rgb: Color) -> RgbSigmoid {
+        let rgb = Color::new(
+            rgb.x.clamp(0., 1.),
+            rgb.y.clamp(0., 1.),
+            rgb.z.clamp(0., 1.),
+        );
+        if rgb.x == rgb.y && rgb.y == rgb.z {
+            let v = rgb.x;
+            return RgbSigmoid {
+                c: [0., 0., (v - 0.5) / (v * (1. - v)).sqrt()],
+            };
+        }
+
+        let maxc = if rgb.x > rgb.y {
+            if rgb.x > rgb.z {
+                0
+            } else {
+                2
+            }
+        } else if rgb.y > rgb.z {
+            1
+        } else {
+            2
+        };
+        let z = rgb[maxc as u32];
+        let x = rgb[((maxc + 1) % 3) as u32] * (RES - 1) as f64 / z;
+        let y = rgb[((maxc + 2) % 3) as u32] * (RES - 1) as f64 / z;
+
+        let xi = (x as usize).min(RES - 2);
+        let yi = (y as usize).min(RES - 2);
+        let zi = (self.scale.partition_point(|&s| s <= z).max(1) - 1).min(RES - 2);
+        let dx = x - xi as f64;
+        let dy = y - yi as f64;
+        let dz = (z - self.scale[zi]) / (self.scale[zi + 1] - self.scale[zi]);
+
+        // 三线性插值
+        let mut c = [0.; 3];
+        for (k, wz) in [(zi, 1. - dz), (zi + 1, dz)].iter() {
+            for (j, wy) in [(yi, 1. - dy), (yi + 1, dy)].iter() {
+                for (i, wx) in [(xi, 1. - dx), (xi + 1, dx)].iter() {
+                    let entry = self.coeffs[((maxc * RES + k) * RES + j) * RES + i];
+                    for n in 0..3 {
+                        c[n] += wx * wy * wz * entry[n];
+                    }
+                }
+            }
+        }
+        RgbSigmoid { c }
+    }
+
+    // 任意非负值(如发光, 吸收系数): 缩放到 [0, 1] 内再转换
+    pub fn unbounded(&self, rgb: Color, lambda: &SampledWavelengths) -> Color {
+        let rgb = Color::new(rgb.x.max(0.), rgb.y.max(0.), rgb.z.max(0.));
+        let m = rgb.x.max(rgb.y).max(rgb.z);
+        if m <= 0. {
+            return Color::new(0., 0., 0.);
+        }
+        lambda.sample(&self.albedo(rgb / (2. * m))) * (2. * m)
+    }
+
+    // 发光: 以 D65 为白色, 使得 RGB(1, 1, 1) 的发光在胶片上仍为白色
+    pub fn illuminant(&self, rgb: Color, lambda: &SampledWavelengths) -> Color {
+        self.unbounded(rgb, lambda) * lambda.sample(&d65) / self.d65_norm
+    }
+
+    pub fn y_integral(&self) -> f64 {
+        self.y_integral
+    }
+}
+
+impl Default for RgbToSpectrum {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spectrum::cie::reflectance_to_srgb;
+
+    #[test]
+    fn albedo_round_trip() {
+        let table = RgbToSpectrum::new();
+        let colors = [
+            Color::new(0.5, 0.5, 0.5),
+            Color::new(0.8, 0.2, 0.1),
+            Color::new(0.1, 0.6, 0.3),
+            Color::new(0.2, 0.3, 0.9),
+            Color::new(0.9, 0.9, 0.2),
+            Color::new(0.05, 0.02, 0.04),
+        ];
+        for rgb in colors.iter() {
+            let spectrum = table.albedo(*rgb);
+            let back = reflectance_to_srgb(|lambda| spectrum.value(lambda));
+            assert!((back - *rgb).len() < 0.01, "{:?} -> {:?}", rgb, back);
+        }
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let table = RgbToSpectrum::new();
+        let path = std::env::temp_dir().join("raytracer_rgb_to_spectrum_test.coeff");
+        let path = path.to_str().unwrap();
+        table.save(path).unwrap();
+        let loaded = RgbToSpectrum::load(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let rgb = Color::new(0.7, 0.4, 0.2);
+        let (a, b) = (table.albedo(rgb), loaded.albedo(rgb));
+        for &lambda in [400., 550., 700.].iter() {
+            assert_eq!(a.value(lambda), b.value(lambda));
+        }
+        assert!(RgbToSpectrum::load("no/such/table.coeff").is_none());
+    }
+}
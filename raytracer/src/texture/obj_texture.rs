@@ -6,6 +6,7 @@ use crate::Hit::Color;
 
 use super::Texture;
 
+#[derive(Clone)]
 pub struct ObjTexture {
     pub u1: f64,
     pub v1: f64,
@@ -64,3 +65,25 @@ impl Texture for ObjTexture {
         ))
     }
 }
+
+// MTL 中的发光: Ke 为发光颜色, map_Ke 为发光贴图(与 Ke 相乘)
+#[derive(Clone)]
+pub struct ObjEmission {
+    pub ke: Color,
+    pub map: Option<ObjTexture>,
+}
+
+impl ObjEmission {
+    pub fn new(ke: Color, map: Option<ObjTexture>) -> Self {
+        Self { ke, map }
+    }
+}
+
+impl Texture for ObjEmission {
+    fn value(&self, beta: f64, gamma: f64, p: &crate::Hit::Point3) -> Option<crate::Hit::Color> {
+        match &self.map {
+            Some(map) => Some(self.ke * map.value(beta, gamma, p)?),
+            None => Some(self.ke),
+        }
+    }
+}